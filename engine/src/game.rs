@@ -0,0 +1,333 @@
+use std::fmt::Display;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{Cell, Generation, Grid, GridError, SafeZone};
+use crate::replay::{Move, Replay, Step};
+use crate::solver::{Knowledge, Solver};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum GameState {
+    Won,
+    Lost,
+    Playing,
+    // never reached through play, frontends use it for games left unfinished
+    Abandoned,
+}
+
+impl Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::Won => write!(f, "Won"),
+            GameState::Lost => write!(f, "Lost"),
+            GameState::Playing => write!(f, "Playing"),
+            GameState::Abandoned => write!(f, "Abandoned"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clicks {
+    pub left: usize,
+    pub right: usize,
+    pub chord: usize,
+}
+
+impl Clicks {
+    pub fn total(&self) -> usize {
+        self.left + self.right + self.chord
+    }
+}
+
+// a board and its outcome, moves are played in turn and time is left to the frontend
+#[derive(Clone)]
+pub struct Game {
+    grid: Grid,
+    state: GameState,
+    clicks: Clicks,
+}
+
+impl Game {
+    pub fn new(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        seed: Option<u64>,
+        safe_zone: SafeZone,
+        generation: Generation,
+    ) -> Result<Self, GridError> {
+        Ok(Self::resume(
+            Grid::new(shape, number_of_mines, seed, safe_zone, generation)?,
+            Clicks::default(),
+        ))
+    }
+
+    pub fn from_rng<R: Rng>(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        safe_zone: SafeZone,
+        generation: Generation,
+        rng: &mut R,
+    ) -> Result<Self, GridError> {
+        Ok(Self::resume(
+            Grid::from_rng(shape, number_of_mines, safe_zone, generation, rng)?,
+            Clicks::default(),
+        ))
+    }
+
+    // a board laid out beforehand, the moves of a replay can be played on it again
+    pub fn from_replay(replay: &Replay) -> Self {
+        Self::resume(
+            Grid::with_mines(replay.shape, &replay.mines, replay.seed, replay.safe_zone, replay.generation),
+            Clicks::default(),
+        )
+    }
+
+    // a game in progress as it was kept
+    pub fn resume(grid: Grid, clicks: Clicks) -> Self {
+        Game {
+            grid,
+            state: GameState::Playing,
+            clicks,
+        }
+    }
+
+    pub fn validate(shape: (usize, usize), number_of_mines: usize) -> Result<(), GridError> {
+        Grid::validate(shape, number_of_mines)
+    }
+
+    // the mines are laid by the first clear, moves off the board and moves after the game is over are ignored
+    pub fn play(&mut self, played: Move) -> GameState {
+        let (Move::Clear(x, y) | Move::Flag(x, y) | Move::QuestionMark(x, y) | Move::ClearAdjacent(x, y)) = played;
+        let (width, height) = self.grid.get_shape();
+        if self.state != GameState::Playing || x >= width || y >= height {
+            return self.state;
+        }
+        let safe = match played {
+            Move::Clear(x, y) => {
+                self.clicks.left += 1;
+                self.grid.set_cleared(x, y)
+            }
+            Move::Flag(x, y) => {
+                self.clicks.right += 1;
+                self.grid.toggle_flagged(x, y);
+                Some(())
+            }
+            Move::QuestionMark(x, y) => {
+                self.clicks.right += 1;
+                self.grid.toggle_question_marked(x, y);
+                Some(())
+            }
+            Move::ClearAdjacent(x, y) => {
+                self.clicks.chord += 1;
+                self.grid.clear_adjacent(x, y)
+            }
+        };
+        if safe.is_none() {
+            self.lose();
+        } else if self.grid.is_initialized() && self.grid.all_cleared() {
+            self.state = GameState::Won;
+        }
+        self.state
+    }
+
+    pub fn clear(&mut self, x: usize, y: usize) -> GameState {
+        self.play(Move::Clear(x, y))
+    }
+
+    pub fn flag(&mut self, x: usize, y: usize) -> GameState {
+        self.play(Move::Flag(x, y))
+    }
+
+    pub fn question_mark(&mut self, x: usize, y: usize) -> GameState {
+        self.play(Move::QuestionMark(x, y))
+    }
+
+    // clears around a number once as many flags as its value surround it
+    pub fn chord(&mut self, x: usize, y: usize) -> GameState {
+        self.play(Move::ClearAdjacent(x, y))
+    }
+
+    // every mine is shown once one is hit
+    fn lose(&mut self) {
+        for x in 0..self.grid.get_shape().0 {
+            for y in 0..self.grid.get_shape().1 {
+                let cell = self.grid.get(x, y);
+                if !cell.cleared && cell.get_value() == -1 {
+                    self.grid.set_cleared(x, y);
+                }
+            }
+        }
+        self.state = GameState::Lost;
+    }
+
+    // the board as the moves of the steps left it
+    pub fn replay(&self, steps: Vec<Step>) -> Option<Replay> {
+        self.grid.is_initialized().then(|| Replay {
+            shape: self.grid.get_shape(),
+            mines: self.grid.get_mines(),
+            seed: self.grid.get_seed(),
+            safe_zone: self.grid.get_safe_zone(),
+            generation: if self.grid.fell_back() {
+                Generation::Classic
+            } else {
+                self.grid.get_generation()
+            },
+            steps,
+        })
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    // the value of hidden cells is there too, bots should only look at knowledge
+    pub fn cell(&self, x: usize, y: usize) -> &Cell {
+        self.grid.get(x, y)
+    }
+
+    pub fn knowledge(&self, x: usize, y: usize) -> Knowledge {
+        self.grid.get(x, y).knowledge()
+    }
+
+    pub fn solver(&self) -> Solver {
+        Solver::new(&self.grid)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        self.grid.get_shape()
+    }
+
+    // nothing is laid out before the first cell is cleared
+    pub fn is_started(&self) -> bool {
+        self.grid.is_initialized()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.grid.get_seed()
+    }
+
+    pub fn safe_zone(&self) -> SafeZone {
+        self.grid.get_safe_zone()
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.grid.get_generation()
+    }
+
+    // no guess was asked for and could not be found, the board may need guessing
+    pub fn fell_back(&self) -> bool {
+        self.grid.fell_back()
+    }
+
+    pub fn remaining_mines(&self) -> isize {
+        self.grid.get_number_of_remaining_mines()
+    }
+
+    pub fn clicks(&self) -> Clicks {
+        self.clicks
+    }
+
+    pub fn bbbv(&self) -> Option<usize> {
+        self.grid.is_initialized().then(|| self.grid.get_3bv())
+    }
+
+    pub fn solved_bbbv(&self) -> usize {
+        self.grid.get_solved_3bv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // mines in the top corners of a 4x3 board
+    //   * 1 1 *
+    //   1 1 1 1
+    //   0 0 0 0
+    fn corners() -> Game {
+        Game::from_replay(&Replay {
+            shape: (4, 3),
+            mines: vec![0, 3],
+            seed: 0,
+            safe_zone: SafeZone::Opening,
+            generation: Generation::Classic,
+            steps: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn clearing_every_free_cell_wins() {
+        let mut game = corners();
+        assert_eq!(game.clear(0, 2), GameState::Playing);
+        assert_eq!(game.knowledge(1, 1), Knowledge::Number(1));
+        assert_eq!(game.knowledge(1, 0), Knowledge::Hidden);
+        game.clear(1, 0);
+        assert_eq!(game.clear(2, 0), GameState::Won);
+        // nothing is played once the game is over
+        assert_eq!(game.clear(0, 0), GameState::Won);
+        assert_eq!(game.clicks(), Clicks { left: 3, right: 0, chord: 0 });
+    }
+
+    #[test]
+    fn a_mine_loses_and_shows_the_others() {
+        let mut game = corners();
+        assert_eq!(game.clear(3, 0), GameState::Lost);
+        assert!(game.cell(0, 0).cleared);
+        assert_eq!(game.knowledge(0, 0), Knowledge::Mine);
+    }
+
+    #[test]
+    fn chord_needs_the_flags_around() {
+        let mut game = corners();
+        game.clear(0, 2);
+        assert_eq!(game.chord(0, 1), GameState::Playing);
+        assert!(!game.cell(1, 0).cleared);
+        game.flag(0, 0);
+        assert_eq!(game.remaining_mines(), 1);
+        game.chord(0, 1);
+        assert!(game.cell(1, 0).cleared);
+        game.flag(3, 0);
+        assert_eq!(game.chord(2, 1), GameState::Won);
+        assert_eq!(game.clicks(), Clicks { left: 1, right: 2, chord: 3 });
+    }
+
+    #[test]
+    fn moves_off_the_board_are_ignored() {
+        let mut game = corners();
+        assert_eq!(game.clear(4, 0), GameState::Playing);
+        assert_eq!(game.flag(0, 3), GameState::Playing);
+        assert_eq!(game.question_mark(usize::MAX, 0), GameState::Playing);
+        assert_eq!(game.chord(4, 3), GameState::Playing);
+        assert_eq!(game.clicks(), Clicks::default());
+        assert_eq!(game.knowledge(0, 2), Knowledge::Hidden);
+    }
+
+    #[test]
+    fn chording_on_a_wrong_flag_loses_and_shows_the_mines() {
+        let mut game = corners();
+        game.clear(0, 2);
+        game.flag(1, 0);
+        assert_eq!(game.chord(0, 1), GameState::Lost);
+        assert_eq!(game.knowledge(0, 0), Knowledge::Mine);
+        assert_eq!(game.knowledge(3, 0), Knowledge::Mine);
+    }
+
+    #[test]
+    fn replay_of_a_new_game() {
+        let mut game = Game::new((9, 9), 10, Some(7), SafeZone::Opening, Generation::Classic).unwrap();
+        assert!(game.replay(Vec::new()).is_none());
+        assert_eq!(game.bbbv(), None);
+        game.clear(4, 4);
+        let replay = game.replay(Vec::new()).unwrap();
+        assert_eq!(replay.seed, 7);
+        assert_eq!(replay.mines.len(), 10);
+        // nothing next to the first click
+        assert!(replay.mines.iter().all(|mine| (mine % 9).abs_diff(4) > 1 || (mine / 9).abs_diff(4) > 1));
+        assert!(game.bbbv().is_some());
+    }
+}
@@ -0,0 +1,971 @@
+use crate::solver::{Knowledge, Solver};
+use rand::{rngs::StdRng, seq::index::sample, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::Display;
+
+// solver steps a no guess board may be searched for before settling for a random one, counted and not timed
+// so the same seed ends with the same board on any machine
+const NO_GUESS_STEPS: usize = 20_000_000;
+// laying out and setting up an attempt costs about this much whatever the size of the board
+const ATTEMPT_STEPS: usize = 1_000;
+
+// beyond this the cells get too small to be clicked
+pub const MAX_SIDE: usize = 100;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridError {
+    Empty,
+    TooLarge,
+    TooManyMines,
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "The grid needs at least one row and one column"),
+            GridError::TooLarge => write!(f, "The grid can not be wider or higher than {}", MAX_SIDE),
+            GridError::TooManyMines => write!(f, "Too many mines, at least one cell must be free"),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+// what the first click is guaranteed not to hit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SafeZone {
+    Cell,
+    Opening,
+    MoveMine,
+    // games recorded before the first click was protected
+    None,
+}
+
+impl SafeZone {
+    pub const SELECTABLE: [SafeZone; 3] = [SafeZone::Cell, SafeZone::Opening, SafeZone::MoveMine];
+    pub const ALL: [SafeZone; 4] = [SafeZone::Cell, SafeZone::Opening, SafeZone::MoveMine, SafeZone::None];
+
+    pub fn label(&self) -> &str {
+        match self {
+            SafeZone::Cell => "safe: cell",
+            SafeZone::Opening => "safe: 3x3",
+            SafeZone::MoveMine => "safe: move",
+            SafeZone::None => "safe: none",
+        }
+    }
+
+    pub fn next(&self, options: &[SafeZone]) -> SafeZone {
+        let index = options.iter().position(|safe_zone| safe_zone == self).unwrap_or(0);
+        options[(index + 1) % options.len()]
+    }
+
+    pub fn from_name(name: &str) -> Option<SafeZone> {
+        SafeZone::ALL
+            .into_iter()
+            .find(|safe_zone| safe_zone.to_string() == name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Generation {
+    Classic,
+    // only boards that can be solved from the first click without guessing
+    NoGuess,
+}
+
+impl Generation {
+    pub fn label(&self) -> &str {
+        match self {
+            Generation::Classic => "classic",
+            Generation::NoGuess => "no guess",
+        }
+    }
+
+    pub fn next(&self) -> Generation {
+        match self {
+            Generation::Classic => Generation::NoGuess,
+            Generation::NoGuess => Generation::Classic,
+        }
+    }
+}
+
+impl Display for SafeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SafeZone::Cell => write!(f, "Cell"),
+            SafeZone::Opening => write!(f, "Opening"),
+            SafeZone::MoveMine => write!(f, "MoveMine"),
+            SafeZone::None => write!(f, "None"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cell {
+    value: i8,
+    pub cleared: bool,
+    pub flagged: bool,
+    pub question_marked: bool,
+}
+
+impl Cell {
+    fn new(value: i8) -> Self {
+        Cell {
+            value,
+            cleared: false,
+            flagged: false,
+            question_marked: false,
+        }
+    }
+
+    pub fn get_value(&self) -> i8 {
+        self.value
+    }
+
+    // what a player can tell of the cell, flags are taken for mines
+    pub fn knowledge(&self) -> Knowledge {
+        match (self.cleared, self.flagged) {
+            (true, _) if self.value >= 0 => Knowledge::Number(self.value as u8),
+            (true, _) | (false, true) => Knowledge::Mine,
+            (false, false) => Knowledge::Hidden,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Grid {
+    grid: Vec<Cell>,
+    shape: (usize, usize),
+    number_of_mines: usize,
+    number_of_cleared: usize,
+    number_of_flags: isize,
+    initialized: bool,
+    seed: u64,
+    safe_zone: SafeZone,
+    generation: Generation,
+    // the 3BV of the board, counted once the mines are laid
+    #[serde(default)]
+    bbbv: usize,
+    // no guess was asked for but no attempt could be solved, the board is a classic one
+    #[serde(default)]
+    fell_back: bool,
+}
+
+impl Grid {
+    pub fn validate(shape: (usize, usize), number_of_mines: usize) -> Result<(), GridError> {
+        if shape.0 == 0 || shape.1 == 0 {
+            return Err(GridError::Empty);
+        }
+        if shape.0 > MAX_SIDE || shape.1 > MAX_SIDE {
+            return Err(GridError::TooLarge);
+        }
+        if number_of_mines >= shape.0 * shape.1 {
+            return Err(GridError::TooManyMines);
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: i8) {
+        self.grid[y * self.shape.0 + x].value = value;
+    }
+
+    fn increment_cell(&mut self, x: usize, y: usize) {
+        if self.get(x, y).value != -1 {
+            self.set(x, y, self.get(x, y).value + 1);
+        }
+    }
+
+    fn is_safe(&self, safe_zone: SafeZone, first_cell: (usize, usize), x: usize, y: usize) -> bool {
+        match safe_zone {
+            SafeZone::Cell => (x, y) == first_cell,
+            SafeZone::Opening => x.abs_diff(first_cell.0) <= 1 && y.abs_diff(first_cell.1) <= 1,
+            SafeZone::MoveMine | SafeZone::None => false,
+        }
+    }
+
+    fn number_of_safe(&self, safe_zone: SafeZone, first_cell: (usize, usize)) -> usize {
+        (0..self.shape.1)
+            .flat_map(|y| (0..self.shape.0).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_safe(safe_zone, first_cell, x, y))
+            .count()
+    }
+
+    // falls back to a smaller safe zone when the board is too dense for the requested one
+    fn effective_safe_zone(&self, first_cell: (usize, usize)) -> SafeZone {
+        let mut safe_zone = self.safe_zone;
+        loop {
+            if self.shape.0 * self.shape.1 - self.number_of_safe(safe_zone, first_cell) >= self.number_of_mines {
+                return safe_zone;
+            }
+            safe_zone = match safe_zone {
+                SafeZone::Opening => SafeZone::Cell,
+                _ => SafeZone::None,
+            };
+        }
+    }
+
+    // exactly number_of_mines, every set of allowed cells being as likely as any other
+    fn place_mines(&self, first_cell: (usize, usize), rng: &mut StdRng) -> Vec<bool> {
+        let safe_zone = self.effective_safe_zone(first_cell);
+        let mut mines = vec![false; self.shape.0 * self.shape.1];
+        let allowed: Vec<usize> = (0..mines.len())
+            .filter(|&index| !self.is_safe(safe_zone, first_cell, index % self.shape.0, index / self.shape.0))
+            .collect();
+        for chosen in sample(rng, allowed.len(), self.number_of_mines) {
+            mines[allowed[chosen]] = true;
+        }
+
+        // classic behaviour, a mine under the first click goes to the first free cell from the top left
+        let first_index = first_cell.1 * self.shape.0 + first_cell.0;
+        if safe_zone == SafeZone::MoveMine && mines[first_index] {
+            if let Some(free) = mines.iter().position(|mine| !mine) {
+                mines[first_index] = false;
+                mines[free] = true;
+            }
+        }
+        mines
+    }
+
+    fn fill(&mut self, mines: &[bool]) {
+        let surroundings: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1)];
+
+        let height = self.shape.1 as isize;
+        let width = self.shape.0 as isize;
+
+        for y in 0..self.shape.1 {
+            for x in 0..self.shape.0 {
+                if mines[y * self.shape.0 + x] {
+                    self.set(x, y, -1);
+                    for s in surroundings {
+                        let (nx, ny) = (x as isize + s.0, y as isize + s.1);
+                        if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                            continue;
+                        }
+                        self.increment_cell(nx as usize, ny as usize);
+                    }
+                }
+            }
+        }
+        self.bbbv = self.count_3bv(false);
+    }
+
+    fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = self.shape;
+        (y.saturating_sub(1)..(y + 2).min(height))
+            .flat_map(move |ny| (x.saturating_sub(1)..(x + 2).min(width)).map(move |nx| (nx, ny)))
+            .filter(move |&cell| cell != (x, y))
+    }
+
+    // one click per opening, and one per number that no opening reveals
+    fn count_3bv(&self, cleared_only: bool) -> usize {
+        let mut revealed = vec![false; self.grid.len()];
+        let mut count = 0;
+
+        for start in 0..self.grid.len() {
+            if self.grid[start].value != 0 || revealed[start] {
+                continue;
+            }
+            let mut cleared = false;
+            let mut stack = vec![(start % self.shape.0, start / self.shape.0)];
+            revealed[start] = true;
+            while let Some((x, y)) = stack.pop() {
+                cleared |= self.get(x, y).cleared;
+                for (nx, ny) in self.neighbours(x, y) {
+                    let index = ny * self.shape.0 + nx;
+                    if revealed[index] {
+                        continue;
+                    }
+                    revealed[index] = true;
+                    if self.grid[index].value == 0 {
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            if cleared || !cleared_only {
+                count += 1;
+            }
+        }
+
+        count
+            + self
+                .grid
+                .iter()
+                .zip(revealed)
+                .filter(|(cell, revealed)| cell.value > 0 && !revealed && (cell.cleared || !cleared_only))
+                .count()
+    }
+
+    // plays the board from the first click using only what the solver can deduce, none once the budget is spent
+    fn solvable(&self, mines: &[bool], first_cell: (usize, usize), budget: &mut usize) -> Option<bool> {
+        *budget = budget.checked_sub(ATTEMPT_STEPS + self.grid.len())?;
+        let mut grid = Grid::empty(
+            self.shape,
+            mines.iter().filter(|&&mine| mine).count(),
+            self.seed,
+            self.safe_zone,
+            Generation::Classic,
+        );
+        grid.fill(mines);
+        grid.initialized = true;
+
+        if grid.set_cleared(first_cell.0, first_cell.1).is_none() {
+            return Some(false);
+        }
+        while !grid.all_cleared() {
+            let deductions = Solver::new(&grid).deduce_within(budget)?;
+            if deductions.safe.is_empty() {
+                return Some(false);
+            }
+            for (x, y) in deductions.mines {
+                if !grid.get(x, y).flagged {
+                    grid.toggle_flagged(x, y);
+                }
+            }
+            for (x, y) in deductions.safe {
+                if grid.set_cleared(x, y).is_none() {
+                    return Some(false);
+                }
+            }
+        }
+        Some(true)
+    }
+
+    // false when no no guess board was found within the budget, the last attempt is laid anyway
+    fn init(&mut self, first_cell: (usize, usize), budget: usize) -> bool {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut mines = self.place_mines(first_cell, &mut rng);
+        let mut solvable = true;
+
+        if self.generation == Generation::NoGuess {
+            let mut budget = budget;
+            loop {
+                match self.solvable(&mines, first_cell, &mut budget) {
+                    Some(true) => break,
+                    Some(false) => mines = self.place_mines(first_cell, &mut rng),
+                    None => {
+                        solvable = false;
+                        break;
+                    }
+                }
+            }
+        }
+        self.fill(&mines);
+        solvable
+    }
+
+    fn empty(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        seed: u64,
+        safe_zone: SafeZone,
+        generation: Generation,
+    ) -> Self {
+        Grid {
+            grid: vec![Cell::new(0); shape.0 * shape.1],
+            shape,
+            number_of_mines,
+            number_of_cleared: 0,
+            number_of_flags: 0,
+            initialized: false,
+            seed,
+            safe_zone,
+            generation,
+            bbbv: 0,
+            fell_back: false,
+        }
+    }
+
+    // the same seed always yields the same board, random seeds are kept to 32 bits
+    // so they stay short enough to be typed back in
+    pub fn new(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        seed: Option<u64>,
+        safe_zone: SafeZone,
+        generation: Generation,
+    ) -> Result<Self, GridError> {
+        Self::validate(shape, number_of_mines)?;
+        let seed = seed.unwrap_or_else(|| rand::thread_rng().gen::<u32>() as u64);
+        Ok(Self::empty(shape, number_of_mines, seed, safe_zone, generation))
+    }
+
+    // the seed is drawn from the given generator, so tests and bots can bring their own
+    pub fn from_rng<R: Rng>(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        safe_zone: SafeZone,
+        generation: Generation,
+        rng: &mut R,
+    ) -> Result<Self, GridError> {
+        Self::new(shape, number_of_mines, Some(rng.gen::<u32>() as u64), safe_zone, generation)
+    }
+
+    // a board laid out beforehand, as when a recorded game is replayed
+    pub fn with_mines(
+        shape: (usize, usize),
+        mines: &[usize],
+        seed: u64,
+        safe_zone: SafeZone,
+        generation: Generation,
+    ) -> Self {
+        let mut grid = Self::empty(shape, mines.len(), seed, safe_zone, generation);
+        let mut layout = vec![false; shape.0 * shape.1];
+        for &mine in mines {
+            layout[mine] = true;
+        }
+        grid.fill(&layout);
+        grid.initialized = true;
+        grid
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &Cell {
+        &self.grid[y * self.shape.0 + x]
+    }
+
+    pub fn all_cleared(&self) -> bool {
+        self.number_of_cleared == self.shape.0 * self.shape.1 - self.number_of_mines
+    }
+
+    fn surrounding_flags(&self, x: usize, y: usize) -> i8 {
+        let mut number_of_flagged: i8 = 0;
+        if x > 0 {
+            number_of_flagged += self.get(x - 1, y).flagged as i8;
+        }
+        if x > 0 && y < self.shape.1 - 1 {
+            number_of_flagged += self.get(x - 1, y + 1).flagged as i8;
+        }
+        if y > 0 {
+            number_of_flagged += self.get(x, y - 1).flagged as i8;
+        }
+        if y > 0 && x < self.shape.0 - 1 {
+            number_of_flagged += self.get(x + 1, y - 1).flagged as i8;
+        }
+        if x > 0 && y > 0 {
+            number_of_flagged += self.get(x - 1, y - 1).flagged as i8;
+        }
+        if x < self.shape.0 - 1 {
+            number_of_flagged += self.get(x + 1, y).flagged as i8;
+        }
+        if y < self.shape.1 - 1 {
+            number_of_flagged += self.get(x, y + 1).flagged as i8;
+        }
+        if x < self.shape.0 - 1 && y < self.shape.1 - 1 {
+            number_of_flagged += self.get(x + 1, y + 1).flagged as i8;
+        }
+        
+        number_of_flagged
+    }
+
+    pub fn clear_adjacent(&mut self, x: usize, y: usize) -> Option<()> {
+        let cell = self.get(x, y);
+        if !cell.cleared {
+            return Some(());
+        }
+        if self.surrounding_flags(x, y) != cell.value {
+            return Some(());
+        }
+
+        if x > 0 {
+            self.set_cleared(x - 1, y)?;
+        }
+        if x > 0 && y < self.shape.1 - 1 {
+            self.set_cleared(x - 1, y + 1)?;
+        }
+        if y > 0 {
+            self.set_cleared(x, y - 1)?;
+        }
+        if y > 0 && x < self.shape.0 - 1 {
+            self.set_cleared(x + 1, y - 1)?;
+        }
+        if x > 0 && y > 0 {
+            self.set_cleared(x - 1, y - 1)?;
+        }
+        if x < self.shape.0 - 1 {
+            self.set_cleared(x + 1, y)?;
+        }
+        if y < self.shape.1 - 1 {
+            self.set_cleared(x, y + 1)?;
+        }
+        if x < self.shape.0 - 1 && y < self.shape.1 - 1 {
+            self.set_cleared(x + 1, y + 1)?;
+        }
+        Some(())
+    }
+
+    pub fn set_cleared(&mut self, x: usize, y: usize) -> Option<()> {
+        if !self.initialized {
+            self.fell_back = !self.init((x, y), NO_GUESS_STEPS);
+            self.initialized = true;
+        }
+        let cell = &mut self.grid[y * self.shape.0 + x];
+        if cell.cleared || cell.flagged {
+            return Some(());
+        }
+        cell.cleared = true;
+        if cell.value == -1 {
+            return None;
+        }
+        self.number_of_cleared += 1;
+        if cell.value == 0 {
+            self.clear_adjacent(x, y)?;
+        }
+        Some(())
+    }
+
+    pub fn toggle_flagged(&mut self, x: usize, y: usize) {
+        if !self.grid[y * self.shape.0 + x].cleared {
+            self.grid[y * self.shape.0 + x].flagged = !(self.grid[y * self.shape.0 + x].flagged);
+            self.number_of_flags += if self.grid[y * self.shape.0 + x].flagged { 1 } else { -1 };
+            self.grid[y * self.shape.0 + x].question_marked = false;
+        }
+    }
+
+    pub fn toggle_question_marked(&mut self, x: usize, y: usize) {
+        if !self.grid[y * self.shape.0 + x].cleared {
+            self.grid[y * self.shape.0 + x].question_marked = !(self.grid[y * self.shape.0 + x].question_marked);
+            self.grid[y * self.shape.0 + x].flagged = false;
+        }
+    }
+
+    pub fn get_shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_safe_zone(&self) -> SafeZone {
+        self.safe_zone
+    }
+
+    // as it was asked for, a no guess board may still have fallen back to a classic one
+    pub fn get_generation(&self) -> Generation {
+        self.generation
+    }
+
+    pub fn fell_back(&self) -> bool {
+        self.fell_back
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub fn get_number_of_mines(&self) -> usize {
+        self.number_of_mines
+    }
+
+    pub fn get_3bv(&self) -> usize {
+        self.bbbv
+    }
+
+    // the part of the 3BV that was cleared so far
+    pub fn get_solved_3bv(&self) -> usize {
+        self.count_3bv(true)
+    }
+
+    pub fn get_mines(&self) -> Vec<usize> {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.value == -1)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn get_number_of_remaining_mines(&self) -> isize {
+        self.number_of_mines as isize - self.number_of_flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    // a board drawn row by row, `*` for a mine
+    fn board(rows: &[&str]) -> Grid {
+        let shape = (rows[0].len(), rows.len());
+        let mines: Vec<usize> = rows
+            .concat()
+            .chars()
+            .enumerate()
+            .filter(|&(_, cell)| cell == '*')
+            .map(|(index, _)| index)
+            .collect();
+        Grid::with_mines(shape, &mines, 0, SafeZone::None, Generation::Classic)
+    }
+
+    fn layout_grid(shape: (usize, usize), layout: &[bool]) -> Grid {
+        let mines: Vec<usize> = (0..layout.len()).filter(|&index| layout[index]).collect();
+        Grid::with_mines(shape, &mines, 0, SafeZone::None, Generation::Classic)
+    }
+
+    fn cleared(grid: &Grid) -> Vec<bool> {
+        grid.grid.iter().map(|cell| cell.cleared).collect()
+    }
+
+    // counted again here rather than trusting fill
+    fn neighbouring_mines(shape: (usize, usize), layout: &[bool], x: usize, y: usize) -> usize {
+        (y.saturating_sub(1)..(y + 2).min(shape.1))
+            .flat_map(|ny| (x.saturating_sub(1)..(x + 2).min(shape.0)).map(move |nx| (nx, ny)))
+            .filter(|&(nx, ny)| (nx, ny) != (x, y) && layout[ny * shape.0 + nx])
+            .count()
+    }
+
+    // what clearing a free cell should uncover, openings spread until they reach numbers
+    fn expected_reveal(shape: (usize, usize), layout: &[bool], start: (usize, usize)) -> Vec<bool> {
+        let mut revealed = vec![false; layout.len()];
+        let mut stack = vec![start];
+        revealed[start.1 * shape.0 + start.0] = true;
+        while let Some((x, y)) = stack.pop() {
+            if neighbouring_mines(shape, layout, x, y) > 0 {
+                continue;
+            }
+            for ny in y.saturating_sub(1)..(y + 2).min(shape.1) {
+                for nx in x.saturating_sub(1)..(x + 2).min(shape.0) {
+                    let index = ny * shape.0 + nx;
+                    if !revealed[index] && !layout[index] {
+                        revealed[index] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        revealed
+    }
+
+    #[test]
+    fn mines_and_numbers() {
+        let grid = board(&[
+            "*..",
+            "..*",
+            "...",
+        ]);
+        let values: Vec<i8> = grid.grid.iter().map(|cell| cell.get_value()).collect();
+        assert_eq!(values, vec![-1, 2, 1, 1, 2, -1, 0, 1, 1]);
+        assert_eq!(grid.get_mines(), vec![0, 5]);
+        assert_eq!(grid.get_number_of_mines(), 2);
+        assert!(grid.is_initialized());
+    }
+
+    #[test]
+    fn clearing_a_zero_opens_up_to_the_numbers() {
+        let mut grid = board(&[
+            "....*",
+            ".....",
+            "...*.",
+            ".....",
+        ]);
+        assert_eq!(grid.set_cleared(0, 0), Some(()));
+        let opened: Vec<bool> = "xxxx.xxxx.xxx..xxx..".chars().map(|cell| cell == 'x').collect();
+        assert_eq!(cleared(&grid), opened);
+        assert!(!grid.all_cleared());
+        // a number only clears itself
+        grid.set_cleared(4, 1);
+        assert_eq!(cleared(&grid).iter().filter(|&&cleared| cleared).count(), 15);
+    }
+
+    #[test]
+    fn zeros_next_to_a_flag_stop_the_flood_fill() {
+        // the opening goes through clear_adjacent, a zero touching a flag counts it and goes no further
+        let mut grid = board(&[
+            "....",
+            "....",
+            "...*",
+        ]);
+        grid.toggle_flagged(1, 0);
+        grid.set_cleared(0, 2);
+        assert!(grid.get(0, 1).cleared && grid.get(1, 1).cleared);
+        assert!(grid.get(2, 1).cleared && grid.get(2, 2).cleared);
+        assert!(!grid.get(1, 0).cleared);
+        assert!(!grid.get(0, 0).cleared && !grid.get(3, 0).cleared);
+        assert!(!grid.all_cleared());
+    }
+
+    #[test]
+    fn all_cleared_exactly_at_win() {
+        let mut grid = board(&[
+            "*.*",
+            "...",
+            "*..",
+        ]);
+        let free = [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)];
+        for (i, &(x, y)) in free.iter().enumerate() {
+            assert!(!grid.all_cleared());
+            assert_eq!(grid.set_cleared(x, y), Some(()), "cell {}", i);
+        }
+        assert!(grid.all_cleared());
+        // flags on the mines are not needed, and clearing a mine loses
+        assert_eq!(grid.set_cleared(0, 0), None);
+    }
+
+    #[test]
+    fn chord_respects_flags() {
+        let mut grid = board(&[
+            "*...",
+            "....",
+            "..*.",
+            "....",
+        ]);
+        // nothing happens on a hidden cell or without enough flags
+        assert_eq!(grid.clear_adjacent(1, 1), Some(()));
+        grid.set_cleared(1, 1);
+        assert_eq!(grid.get(1, 1).get_value(), 2);
+        grid.toggle_flagged(0, 0);
+        assert_eq!(grid.clear_adjacent(1, 1), Some(()));
+        assert!(!grid.get(1, 0).cleared);
+
+        // the right flags clear every other neighbour
+        grid.toggle_flagged(2, 2);
+        assert_eq!(grid.clear_adjacent(1, 1), Some(()));
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2)] {
+            assert!(grid.get(x, y).cleared, "({}, {})", x, y);
+        }
+        assert!(!grid.get(0, 0).cleared && !grid.get(2, 2).cleared);
+    }
+
+    #[test]
+    fn a_wrong_flag_makes_chord_hit_a_mine() {
+        let mut grid = board(&[
+            "*..",
+            "...",
+            "...",
+        ]);
+        grid.set_cleared(1, 1);
+        grid.toggle_flagged(2, 2);
+        assert_eq!(grid.clear_adjacent(1, 1), None);
+        assert!(grid.get(0, 0).cleared);
+    }
+
+    #[test]
+    fn flags_and_question_marks() {
+        let mut grid = board(&[
+            "*.",
+            "..",
+        ]);
+        grid.toggle_flagged(0, 0);
+        assert_eq!(grid.get_number_of_remaining_mines(), 0);
+        // a flagged cell can not be cleared
+        assert_eq!(grid.set_cleared(0, 0), Some(()));
+        grid.toggle_question_marked(0, 0);
+        assert!(!grid.get(0, 0).flagged && grid.get(0, 0).question_marked);
+        grid.set_cleared(1, 1);
+        grid.toggle_flagged(1, 1);
+        assert!(!grid.get(1, 1).flagged);
+    }
+
+    #[test]
+    fn first_click_is_safe() {
+        for seed in 0..50 {
+            let mut cell = Grid::new((9, 9), 10, Some(seed), SafeZone::Cell, Generation::Classic).unwrap();
+            assert_eq!(cell.set_cleared(0, 0), Some(()));
+
+            let mut opening = Grid::new((9, 9), 10, Some(seed), SafeZone::Opening, Generation::Classic).unwrap();
+            opening.set_cleared(4, 4);
+            assert_eq!(opening.get(4, 4).get_value(), 0);
+
+            let mut move_mine = Grid::new((9, 9), 70, Some(seed), SafeZone::MoveMine, Generation::Classic).unwrap();
+            assert_eq!(move_mine.set_cleared(8, 8), Some(()));
+        }
+    }
+
+    #[test]
+    fn dense_boards_keep_the_first_cell() {
+        // no room for a 3x3 opening, only the clicked cell is kept free
+        let mut grid = Grid::new((3, 3), 8, Some(1), SafeZone::Opening, Generation::Classic).unwrap();
+        assert_eq!(grid.set_cleared(1, 1), Some(()));
+        assert!(grid.is_initialized());
+    }
+
+    #[test]
+    fn no_guess_boards_are_solvable() {
+        let mut grid = Grid::new((9, 9), 10, Some(3), SafeZone::Opening, Generation::NoGuess).unwrap();
+        grid.set_cleared(4, 4);
+        assert!(!grid.fell_back());
+        let layout: Vec<bool> = grid.grid.iter().map(|cell| cell.get_value() == -1).collect();
+        let mut unbounded = usize::MAX;
+        assert_eq!(grid.solvable(&layout, (4, 4), &mut unbounded), Some(true));
+
+        let mut again = Grid::new((9, 9), 10, Some(3), SafeZone::Opening, Generation::NoGuess).unwrap();
+        again.set_cleared(4, 4);
+        assert_eq!(again.get_mines(), grid.get_mines());
+    }
+
+    #[test]
+    fn no_guess_falls_back_when_nothing_can_be_solved() {
+        // a mine in every cell but the clicked one and a corner, the corner can never be told apart
+        let mut grid = Grid::new((3, 3), 7, Some(1), SafeZone::Cell, Generation::NoGuess).unwrap();
+        grid.set_cleared(1, 1);
+        assert!(grid.fell_back());
+        assert_eq!(grid.get_generation(), Generation::NoGuess);
+    }
+
+    // counted in solver steps, so even a single attempt on the largest board is cut short
+    #[test]
+    fn large_no_guess_boards_give_up_within_their_budget() {
+        let sparse = Grid::new((MAX_SIDE, MAX_SIDE), 1000, Some(1), SafeZone::Opening, Generation::NoGuess).unwrap();
+        let mines = sparse.place_mines((50, 50), &mut StdRng::seed_from_u64(1));
+        assert_eq!(sparse.solvable(&mines, (50, 50), &mut 100_000), None);
+
+        let mut grid = Grid::new((MAX_SIDE, MAX_SIDE), 2000, Some(1), SafeZone::Opening, Generation::NoGuess).unwrap();
+        assert!(!grid.init((50, 50), 100_000));
+        assert_eq!(grid.get_mines().len(), 2000);
+        assert_ne!(grid.get(50, 50).get_value(), -1);
+    }
+
+    #[test]
+    fn boards_are_checked() {
+        assert_eq!(Grid::validate((0, 5), 0).err(), Some(GridError::Empty));
+        assert_eq!(Grid::validate((MAX_SIDE + 1, 5), 1).err(), Some(GridError::TooLarge));
+        assert_eq!(Grid::validate((3, 3), 9).err(), Some(GridError::TooManyMines));
+        assert!(Grid::validate((3, 3), 8).is_ok());
+    }
+
+    #[test]
+    fn boards_from_the_same_generator_match() {
+        let board = |rng: &mut StdRng| {
+            let mut grid = Grid::from_rng((16, 16), 40, SafeZone::Opening, Generation::Classic, rng).unwrap();
+            grid.set_cleared(8, 8);
+            grid.get_mines()
+        };
+        let (mut first, mut second) = (StdRng::seed_from_u64(9), StdRng::seed_from_u64(9));
+        assert_eq!(board(&mut first), board(&mut second));
+        assert_ne!(board(&mut first), board(&mut StdRng::seed_from_u64(10)));
+    }
+
+    #[test]
+    fn dense_and_large_boards() {
+        let cells = MAX_SIDE * MAX_SIDE;
+        let mut dense = Grid::new((MAX_SIDE, MAX_SIDE), cells - 1, Some(5), SafeZone::Opening, Generation::Classic).unwrap();
+        assert_eq!(dense.set_cleared(50, 50), Some(()));
+        assert_eq!(dense.get_mines().len(), cells - 1);
+        assert!(dense.all_cleared());
+
+        let mut large = Grid::new((MAX_SIDE, MAX_SIDE), 2000, Some(5), SafeZone::Opening, Generation::Classic).unwrap();
+        large.set_cleared(0, 0);
+        assert_eq!(large.get_mines().len(), 2000);
+    }
+
+    #[test]
+    fn mines_are_spread_evenly() {
+        // one mine among the 8 cells around a safe corner, each should get it about 1 time in 8
+        let mut counts = [0; 9];
+        for seed in 0..1600 {
+            let mut grid = Grid::new((3, 3), 1, Some(seed), SafeZone::Cell, Generation::Classic).unwrap();
+            grid.set_cleared(0, 0);
+            counts[grid.get_mines()[0]] += 1;
+        }
+        assert_eq!(counts[0], 0);
+        assert!(counts[1..].iter().all(|&count| (140..260).contains(&count)), "{:?}", counts);
+    }
+
+    // a shape with a layout of mines leaving at least one cell free
+    fn layouts() -> impl Strategy<Value = ((usize, usize), Vec<bool>)> {
+        (1..=16usize, 1..=16usize)
+            .prop_flat_map(|shape| {
+                (
+                    Just(shape),
+                    proptest::collection::vec(proptest::bool::weighted(0.2), shape.0 * shape.1),
+                )
+            })
+            .prop_filter("no free cell", |(_, layout)| layout.contains(&false))
+    }
+
+    // a random board with the cell of its first click
+    fn boards() -> impl Strategy<Value = ((usize, usize), usize, u64, SafeZone, (usize, usize))> {
+        (1..=30usize, 1..=30usize).prop_flat_map(|(width, height)| {
+            (
+                Just((width, height)),
+                0..width * height,
+                any::<u64>(),
+                select(SafeZone::SELECTABLE.to_vec()),
+                (0..width, 0..height),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn numbers_count_the_neighbouring_mines((shape, number_of_mines, seed, safe_zone, first) in boards()) {
+            let mut grid = Grid::new(shape, number_of_mines, Some(seed), safe_zone, Generation::Classic).unwrap();
+            prop_assert_eq!(grid.set_cleared(first.0, first.1), Some(()));
+            let layout: Vec<bool> = grid.grid.iter().map(|cell| cell.get_value() == -1).collect();
+            prop_assert!(!layout[first.1 * shape.0 + first.0]);
+            for y in 0..shape.1 {
+                for x in 0..shape.0 {
+                    if !layout[y * shape.0 + x] {
+                        prop_assert_eq!(grid.get(x, y).get_value() as usize, neighbouring_mines(shape, &layout, x, y));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn the_same_seed_lays_the_same_mines((shape, number_of_mines, seed, safe_zone, first) in boards()) {
+            let mut a = Grid::new(shape, number_of_mines, Some(seed), safe_zone, Generation::Classic).unwrap();
+            let mut b = Grid::new(shape, number_of_mines, Some(seed), safe_zone, Generation::Classic).unwrap();
+            a.set_cleared(first.0, first.1);
+            b.set_cleared(first.0, first.1);
+            prop_assert_eq!(a.get_mines(), b.get_mines());
+        }
+
+        #[test]
+        fn exactly_the_requested_mines((shape, number_of_mines, seed, safe_zone, first) in boards()) {
+            let mut grid = Grid::new(shape, number_of_mines, Some(seed), safe_zone, Generation::Classic).unwrap();
+            grid.set_cleared(first.0, first.1);
+            prop_assert_eq!(grid.get_mines().len(), number_of_mines);
+        }
+
+        #[test]
+        fn flood_fill_uncovers_the_opening((shape, layout) in layouts(), pick in any::<proptest::sample::Index>()) {
+            let free: Vec<usize> = (0..layout.len()).filter(|&index| !layout[index]).collect();
+            let start = free[pick.index(free.len())];
+            let start = (start % shape.0, start / shape.0);
+            let mut grid = layout_grid(shape, &layout);
+            prop_assert_eq!(grid.set_cleared(start.0, start.1), Some(()));
+            prop_assert_eq!(cleared(&grid), expected_reveal(shape, &layout, start));
+        }
+
+        #[test]
+        fn all_cleared_once_every_free_cell_is((shape, layout) in layouts(), order in any::<u64>()) {
+            let mut free: Vec<usize> = (0..layout.len()).filter(|&index| !layout[index]).collect();
+            free.sort_by_key(|&index| (index as u64).wrapping_mul(order | 1).rotate_left(17));
+            let mut grid = layout_grid(shape, &layout);
+            for index in free {
+                prop_assert!(!grid.all_cleared() || grid.get(index % shape.0, index / shape.0).cleared);
+                prop_assert_eq!(grid.set_cleared(index % shape.0, index / shape.0), Some(()));
+            }
+            prop_assert!(grid.all_cleared());
+        }
+
+        #[test]
+        fn chord_with_the_right_flags_is_safe((shape, layout) in layouts(), pick in any::<proptest::sample::Index>()) {
+            let free: Vec<usize> = (0..layout.len()).filter(|&index| !layout[index]).collect();
+            let start = free[pick.index(free.len())];
+            let (x, y) = (start % shape.0, start / shape.0);
+            let mut grid = layout_grid(shape, &layout);
+            grid.set_cleared(x, y);
+            let mines: Vec<(usize, usize)> = grid.neighbours(x, y).filter(|&(nx, ny)| layout[ny * shape.0 + nx]).collect();
+
+            // one flag short, nothing is cleared
+            if let Some((&(mx, my), rest)) = mines.split_last() {
+                for &(nx, ny) in rest {
+                    grid.toggle_flagged(nx, ny);
+                }
+                let before = cleared(&grid);
+                prop_assert_eq!(grid.clear_adjacent(x, y), Some(()));
+                prop_assert_eq!(cleared(&grid), before);
+                grid.toggle_flagged(mx, my);
+            }
+            prop_assert_eq!(grid.clear_adjacent(x, y), Some(()));
+            let neighbours: Vec<(usize, usize)> = grid.neighbours(x, y).collect();
+            for (nx, ny) in neighbours {
+                prop_assert_eq!(grid.get(nx, ny).cleared, !layout[ny * shape.0 + nx]);
+            }
+        }
+    }
+}
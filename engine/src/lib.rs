@@ -0,0 +1,10 @@
+// the rules of minezweeper without a frontend, boards are played through Game one move at a time
+mod game;
+pub mod grid;
+pub mod replay;
+pub mod solver;
+
+pub use game::{Clicks, Game, GameState};
+pub use grid::{Cell, Generation, Grid, GridError, SafeZone, MAX_SIDE};
+pub use replay::{Move, Replay, Step};
+pub use solver::{Knowledge, Solver};
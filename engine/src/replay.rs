@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{Generation, SafeZone};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Move {
+    Clear(usize, usize),
+    Flag(usize, usize),
+    QuestionMark(usize, usize),
+    ClearAdjacent(usize, usize),
+}
+
+// time is counted in seconds from the first click
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub time: f32,
+    pub played: Move,
+}
+
+// the mines are kept as they were laid out, so a replay does not depend on how boards are generated
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub shape: (usize, usize),
+    pub mines: Vec<usize>,
+    pub seed: u64,
+    pub safe_zone: SafeZone,
+    pub generation: Generation,
+    pub steps: Vec<Step>,
+}
@@ -0,0 +1,657 @@
+use crate::grid::Grid;
+
+// nodes the enumeration may visit before the probabilities are only estimated, keeps a hint well under a frame
+const ENUMERATION_BUDGET: usize = 200_000;
+
+// what the player can see of a cell
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Knowledge {
+    Hidden,
+    Mine,
+    Number(u8),
+}
+
+// the number of mines among a set of hidden cells
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Constraint {
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+#[derive(Default, Debug)]
+pub struct Deductions {
+    pub safe: Vec<(usize, usize)>,
+    pub mines: Vec<(usize, usize)>,
+}
+
+#[derive(Default, Debug)]
+pub struct Analysis {
+    pub safe: Vec<(usize, usize)>,
+    pub mines: Vec<(usize, usize)>,
+    // mine probability of every hidden cell next to a number that is neither safe nor a mine for sure
+    pub probabilities: Vec<((usize, usize), f64)>,
+    // mine probability shared by the hidden cells that touch no number
+    pub interior: Vec<(usize, usize)>,
+    pub interior_probability: Option<f64>,
+    // false when the placements were too many to count and the probabilities are estimates
+    pub exact: bool,
+}
+
+// every way the mines of a group of linked cells can be placed, counted by how many mines they use
+struct Component {
+    cells: Vec<usize>,
+    // solutions[k] ways to place k mines, cell_mines[k][i] of them with a mine on cells[i]
+    solutions: Vec<f64>,
+    cell_mines: Vec<Vec<f64>>,
+}
+
+// deduces what it can from the cleared numbers and flags of a grid, flags are trusted to be mines
+pub struct Solver {
+    shape: (usize, usize),
+    cells: Vec<Knowledge>,
+    number_of_mines: usize,
+}
+
+impl Solver {
+    pub fn from_view(shape: (usize, usize), cells: Vec<Knowledge>, number_of_mines: usize) -> Self {
+        assert_eq!(cells.len(), shape.0 * shape.1, "the view does not match the shape");
+        Solver {
+            shape,
+            cells,
+            number_of_mines,
+        }
+    }
+
+    pub fn new(grid: &Grid) -> Self {
+        let shape = grid.get_shape();
+        let mut cells = Vec::with_capacity(shape.0 * shape.1);
+        for y in 0..shape.1 {
+            for x in 0..shape.0 {
+                cells.push(grid.get(x, y).knowledge());
+            }
+        }
+        Solver {
+            shape,
+            cells,
+            number_of_mines: grid.get_number_of_mines(),
+        }
+    }
+
+    fn position(&self, index: usize) -> (usize, usize) {
+        (index % self.shape.0, index / self.shape.0)
+    }
+
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.position(index);
+        (y.saturating_sub(1)..(y + 2).min(self.shape.1))
+            .flat_map(move |ny| (x.saturating_sub(1)..(x + 2).min(self.shape.0)).map(move |nx| (nx, ny)))
+            .filter(move |&(nx, ny)| (nx, ny) != (x, y))
+            .map(|(nx, ny)| ny * self.shape.0 + nx)
+    }
+
+    fn constraints(&self, safe: &[bool], mine: &[bool]) -> Vec<Constraint> {
+        let mut constraints: Vec<Constraint> = Vec::new();
+        for (index, knowledge) in self.cells.iter().enumerate() {
+            let Knowledge::Number(value) = *knowledge else {
+                continue;
+            };
+            let mut cells = Vec::new();
+            let mut known_mines = 0;
+            for neighbour in self.neighbours(index) {
+                if self.cells[neighbour] == Knowledge::Mine || mine[neighbour] {
+                    known_mines += 1;
+                } else if self.cells[neighbour] == Knowledge::Hidden && !safe[neighbour] {
+                    cells.push(neighbour);
+                }
+            }
+            // more flags than the number means a wrong flag, nothing can be trusted around it
+            if cells.is_empty() || known_mines > value as usize {
+                continue;
+            }
+            let constraint = Constraint {
+                cells,
+                mines: value as usize - known_mines,
+            };
+            if !constraints.contains(&constraint) {
+                constraints.push(constraint);
+            }
+        }
+        constraints
+    }
+
+    fn mark(cells: &[usize], as_mine: bool, safe: &mut [bool], mine: &mut [bool]) -> bool {
+        let mut changed = false;
+        for &cell in cells {
+            if as_mine && !mine[cell] {
+                mine[cell] = true;
+                changed = true;
+            } else if !as_mine && !safe[cell] {
+                safe[cell] = true;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn deduce(&self) -> Deductions {
+        let mut unbounded = usize::MAX;
+        self.deduce_within(&mut unbounded).expect("an unbounded budget is never spent")
+    }
+
+    // none once the budget runs out, each round of propagation costs the cells it reads and the pairs of numbers
+    // it compares, so the same board always costs the same
+    pub fn deduce_within(&self, budget: &mut usize) -> Option<Deductions> {
+        let (safe, mine) = self.propagate_within(budget)?;
+        Some(Deductions {
+            safe: self.positions(&safe),
+            mines: self.positions(&mine),
+        })
+    }
+
+    fn positions(&self, marked: &[bool]) -> Vec<(usize, usize)> {
+        (0..self.cells.len())
+            .filter(|&index| marked[index])
+            .map(|index| self.position(index))
+            .collect()
+    }
+
+    fn propagate(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut unbounded = usize::MAX;
+        self.propagate_within(&mut unbounded).expect("an unbounded budget is never spent")
+    }
+
+    fn propagate_within(&self, budget: &mut usize) -> Option<(Vec<bool>, Vec<bool>)> {
+        let mut safe = vec![false; self.cells.len()];
+        let mut mine = vec![false; self.cells.len()];
+
+        loop {
+            let constraints = self.constraints(&safe, &mine);
+            let steps = self.cells.len() + constraints.len() * constraints.len();
+            *budget = budget.checked_sub(steps)?;
+            let mut changed = false;
+
+            for constraint in constraints.iter() {
+                if constraint.mines == 0 {
+                    changed |= Self::mark(&constraint.cells, false, &mut safe, &mut mine);
+                } else if constraint.mines == constraint.cells.len() {
+                    changed |= Self::mark(&constraint.cells, true, &mut safe, &mut mine);
+                }
+            }
+
+            // when the cells of one number are all around another, the difference holds the difference in mines
+            if !changed {
+                for small in constraints.iter() {
+                    for large in constraints.iter() {
+                        if small == large
+                            || small.mines > large.mines
+                            || !small.cells.iter().all(|cell| large.cells.contains(cell))
+                        {
+                            continue;
+                        }
+                        let difference: Vec<usize> = large
+                            .cells
+                            .iter()
+                            .filter(|cell| !small.cells.contains(cell))
+                            .copied()
+                            .collect();
+                        let mines = large.mines - small.mines;
+                        if difference.is_empty() {
+                            continue;
+                        }
+                        if mines == 0 {
+                            changed |= Self::mark(&difference, false, &mut safe, &mut mine);
+                        } else if mines == difference.len() {
+                            changed |= Self::mark(&difference, true, &mut safe, &mut mine);
+                        }
+                    }
+                }
+            }
+
+            // the mine counter settles the board once all remaining mines or all remaining safe cells are found
+            if !changed {
+                let unknown: Vec<usize> = (0..self.cells.len())
+                    .filter(|&index| self.cells[index] == Knowledge::Hidden && !safe[index] && !mine[index])
+                    .collect();
+                let remaining = self.number_of_mines.saturating_sub(self.found_mines(&mine));
+                if !unknown.is_empty() && remaining == 0 {
+                    changed |= Self::mark(&unknown, false, &mut safe, &mut mine);
+                } else if !unknown.is_empty() && remaining == unknown.len() {
+                    changed |= Self::mark(&unknown, true, &mut safe, &mut mine);
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+        Some((safe, mine))
+    }
+
+    fn found_mines(&self, mine: &[bool]) -> usize {
+        self.cells
+            .iter()
+            .zip(mine.iter())
+            .filter(|(knowledge, &mine)| **knowledge == Knowledge::Mine || mine)
+            .count()
+    }
+
+    // splits the cells of the constraints into groups that do not influence each other
+    fn components(constraints: &[Constraint]) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let mut components: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+        let mut assigned = vec![false; constraints.len()];
+        for start in 0..constraints.len() {
+            if assigned[start] {
+                continue;
+            }
+            assigned[start] = true;
+            let mut constraint_indices = vec![start];
+            let mut cells: Vec<usize> = Vec::new();
+            let mut next = 0;
+            // breadth first, so neighbouring cells are enumerated one after the other
+            while next < constraint_indices.len() {
+                for &cell in constraints[constraint_indices[next]].cells.iter() {
+                    if cells.contains(&cell) {
+                        continue;
+                    }
+                    cells.push(cell);
+                    for (other, constraint) in constraints.iter().enumerate() {
+                        if !assigned[other] && constraint.cells.contains(&cell) {
+                            assigned[other] = true;
+                            constraint_indices.push(other);
+                        }
+                    }
+                }
+                next += 1;
+            }
+            components.push((cells, constraint_indices));
+        }
+        components
+    }
+
+    // none once the budget runs out, a long frontier of loose numbers has exponentially many placements
+    fn enumerate(
+        cells: &[usize],
+        constraints: &[&Constraint],
+        max_mines: usize,
+        budget: &mut usize,
+    ) -> Option<Component> {
+        let cell_constraints: Vec<Vec<usize>> = cells
+            .iter()
+            .map(|cell| {
+                (0..constraints.len())
+                    .filter(|&index| constraints[index].cells.contains(cell))
+                    .collect()
+            })
+            .collect();
+        let mut component = Component {
+            cells: cells.to_vec(),
+            solutions: vec![0.0; cells.len() + 1],
+            cell_mines: vec![vec![0.0; cells.len()]; cells.len() + 1],
+        };
+        let mut placed: Vec<usize> = vec![0; constraints.len()];
+        let mut open: Vec<usize> = constraints.iter().map(|constraint| constraint.cells.len()).collect();
+        let mut assignment = vec![false; cells.len()];
+
+        #[allow(clippy::too_many_arguments)]
+        fn place(
+            position: usize,
+            mines: usize,
+            max_mines: usize,
+            constraints: &[&Constraint],
+            cell_constraints: &[Vec<usize>],
+            placed: &mut [usize],
+            open: &mut [usize],
+            assignment: &mut [bool],
+            component: &mut Component,
+            budget: &mut usize,
+        ) -> bool {
+            if *budget == 0 {
+                return false;
+            }
+            *budget -= 1;
+            if position == assignment.len() {
+                component.solutions[mines] += 1.0;
+                for (index, &mine) in assignment.iter().enumerate() {
+                    if mine {
+                        component.cell_mines[mines][index] += 1.0;
+                    }
+                }
+                return true;
+            }
+            for mine in [false, true] {
+                if mine && mines == max_mines {
+                    continue;
+                }
+                let fits = cell_constraints[position].iter().all(|&index| {
+                    let placed = placed[index] + mine as usize;
+                    placed <= constraints[index].mines && placed + open[index] > constraints[index].mines
+                });
+                if !fits {
+                    continue;
+                }
+                for &index in cell_constraints[position].iter() {
+                    placed[index] += mine as usize;
+                    open[index] -= 1;
+                }
+                assignment[position] = mine;
+                let finished = place(
+                    position + 1,
+                    mines + mine as usize,
+                    max_mines,
+                    constraints,
+                    cell_constraints,
+                    placed,
+                    open,
+                    assignment,
+                    component,
+                    budget,
+                );
+                for &index in cell_constraints[position].iter() {
+                    placed[index] -= mine as usize;
+                    open[index] += 1;
+                }
+                if !finished {
+                    return false;
+                }
+            }
+            true
+        }
+
+        place(
+            0,
+            0,
+            max_mines,
+            constraints,
+            &cell_constraints,
+            &mut placed,
+            &mut open,
+            &mut assignment,
+            &mut component,
+            budget,
+        )
+        .then_some(component)
+    }
+
+    fn convolve(distributions: &[&Vec<f64>]) -> Vec<f64> {
+        let mut result = vec![1.0];
+        for distribution in distributions {
+            let mut next = vec![0.0; result.len() + distribution.len() - 1];
+            for (i, a) in result.iter().enumerate() {
+                for (j, b) in distribution.iter().enumerate() {
+                    next[i + j] += a * b;
+                }
+            }
+            result = next;
+        }
+        result
+    }
+
+    fn ln_binomial(n: usize, k: usize) -> f64 {
+        (0..k).map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln()).sum()
+    }
+
+    // exact probabilities, every placement of the remaining mines consistent with the board is equally likely
+    pub fn analyse(&self) -> Analysis {
+        self.analyse_within(ENUMERATION_BUDGET)
+    }
+
+    fn analyse_within(&self, mut budget: usize) -> Analysis {
+        let (mut safe, mut mine) = self.propagate();
+        let constraints = self.constraints(&safe, &mine);
+        let remaining = self.number_of_mines.saturating_sub(self.found_mines(&mine));
+
+        let mut frontier = vec![false; self.cells.len()];
+        for constraint in constraints.iter() {
+            for &cell in constraint.cells.iter() {
+                frontier[cell] = true;
+            }
+        }
+        let interior: Vec<usize> = (0..self.cells.len())
+            .filter(|&index| {
+                self.cells[index] == Knowledge::Hidden && !safe[index] && !mine[index] && !frontier[index]
+            })
+            .collect();
+
+        let components: Option<Vec<Component>> = Self::components(&constraints)
+            .into_iter()
+            .map(|(cells, constraint_indices)| {
+                let component_constraints: Vec<&Constraint> =
+                    constraint_indices.iter().map(|&index| &constraints[index]).collect();
+                Self::enumerate(&cells, &component_constraints, remaining, &mut budget)
+            })
+            .collect();
+        let Some(components) = components else {
+            return self.estimate(&safe, &mine, &constraints, &frontier, &interior, remaining);
+        };
+
+        // the interior cells take whatever mines the frontier leaves, in any of binomial(interior, left) ways
+        let ln_weights: Vec<Option<f64>> = (0..=remaining)
+            .map(|frontier_mines| {
+                let left = remaining - frontier_mines;
+                (left <= interior.len()).then(|| Self::ln_binomial(interior.len(), left))
+            })
+            .collect();
+        let max_ln_weight = ln_weights.iter().flatten().cloned().fold(f64::MIN, f64::max);
+        let weight = |frontier_mines: usize| -> f64 {
+            match ln_weights.get(frontier_mines) {
+                Some(Some(ln_weight)) => (ln_weight - max_ln_weight).exp(),
+                _ => 0.0,
+            }
+        };
+
+        let all: Vec<&Vec<f64>> = components.iter().map(|component| &component.solutions).collect();
+        let combined = Self::convolve(&all);
+        let total: f64 = combined
+            .iter()
+            .enumerate()
+            .map(|(frontier_mines, solutions)| solutions * weight(frontier_mines))
+            .sum();
+
+        let mut analysis = Analysis {
+            exact: true,
+            ..Analysis::default()
+        };
+        // a board with wrong flags has no solution, only the propagated certainties are left
+        if total > 0.0 {
+            for (index, component) in components.iter().enumerate() {
+                let others: Vec<&Vec<f64>> = components
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, component)| &component.solutions)
+                    .collect();
+                let others = Self::convolve(&others);
+                for (cell_index, &cell) in component.cells.iter().enumerate() {
+                    let mut with_mine = 0.0;
+                    let mut without_mine = 0.0;
+                    for (mines, solutions) in component.solutions.iter().enumerate() {
+                        let cell_mines = component.cell_mines[mines][cell_index];
+                        for (other_mines, other_solutions) in others.iter().enumerate() {
+                            let weight = other_solutions * weight(mines + other_mines);
+                            with_mine += cell_mines * weight;
+                            without_mine += (solutions - cell_mines) * weight;
+                        }
+                    }
+                    if with_mine == 0.0 {
+                        safe[cell] = true;
+                    } else if without_mine == 0.0 {
+                        mine[cell] = true;
+                    } else {
+                        analysis
+                            .probabilities
+                            .push((self.position(cell), with_mine / total));
+                    }
+                }
+            }
+
+            if !interior.is_empty() {
+                let expected_mines: f64 = combined
+                    .iter()
+                    .enumerate()
+                    .map(|(frontier_mines, solutions)| {
+                        solutions * weight(frontier_mines) * remaining.saturating_sub(frontier_mines) as f64
+                    })
+                    .sum();
+                let probability = expected_mines / total / interior.len() as f64;
+                if probability == 0.0 || probability == 1.0 {
+                    for &cell in interior.iter() {
+                        safe[cell] = probability == 0.0;
+                        mine[cell] = probability == 1.0;
+                    }
+                } else {
+                    analysis.interior = interior.iter().map(|&cell| self.position(cell)).collect();
+                    analysis.interior_probability = Some(probability);
+                }
+            }
+        }
+
+        analysis.safe = self.positions(&safe);
+        analysis.mines = self.positions(&mine);
+        analysis
+    }
+
+    // only the certain deductions are exact, a frontier cell takes the average density of its numbers
+    // and the interior shares the mines the frontier is expected to leave
+    fn estimate(
+        &self,
+        safe: &[bool],
+        mine: &[bool],
+        constraints: &[Constraint],
+        frontier: &[bool],
+        interior: &[usize],
+        remaining: usize,
+    ) -> Analysis {
+        let mut analysis = Analysis {
+            safe: self.positions(safe),
+            mines: self.positions(mine),
+            ..Analysis::default()
+        };
+        for cell in (0..self.cells.len()).filter(|&index| frontier[index]) {
+            let densities: Vec<f64> = constraints
+                .iter()
+                .filter(|constraint| constraint.cells.contains(&cell))
+                .map(|constraint| constraint.mines as f64 / constraint.cells.len() as f64)
+                .collect();
+            let probability = densities.iter().sum::<f64>() / densities.len() as f64;
+            analysis.probabilities.push((self.position(cell), probability));
+        }
+        if !interior.is_empty() {
+            let expected_mines: f64 = analysis.probabilities.iter().map(|(_, probability)| probability).sum();
+            let probability = ((remaining as f64 - expected_mines) / interior.len() as f64).clamp(0.0, 1.0);
+            analysis.interior = interior.iter().map(|&cell| self.position(cell)).collect();
+            analysis.interior_probability = Some(probability);
+        }
+        analysis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // rows of the view, # is hidden, * a flag and a digit a cleared number
+    fn solver(rows: &[&str], number_of_mines: usize) -> Solver {
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                '#' => Knowledge::Hidden,
+                '*' => Knowledge::Mine,
+                digit => Knowledge::Number(digit.to_digit(10).unwrap() as u8),
+            })
+            .collect();
+        Solver::from_view((rows[0].len(), rows.len()), cells, number_of_mines)
+    }
+
+    fn probability(analysis: &Analysis, cell: (usize, usize)) -> f64 {
+        analysis
+            .probabilities
+            .iter()
+            .find(|(position, _)| *position == cell)
+            .map(|(_, probability)| *probability)
+            .unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn single_numbers() {
+        // the first 1 sees one hidden cell, the second is then satisfied
+        let deductions = solver(&["1#1#"], 5).deduce();
+        assert_eq!(deductions.mines, vec![(1, 0)]);
+        assert_eq!(deductions.safe, vec![(3, 0)]);
+
+        let deductions = solver(&["*1#", "11#"], 5).deduce();
+        assert_eq!(deductions.mines, vec![]);
+        assert_eq!(deductions.safe, vec![(2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn one_number_inside_another() {
+        // no number decides alone, the 1s inside the 2 put its other mine on the far side
+        let deductions = solver(&["###", "121"], 2).deduce();
+        assert_eq!(deductions.mines, vec![(0, 0), (2, 0)]);
+        assert_eq!(deductions.safe, vec![(1, 0)]);
+    }
+
+    #[test]
+    fn exact_frontier_probabilities() {
+        // the single mine is on one of the two cells both numbers see, the others are safe
+        let analysis = solver(&["1##", "##1"], 1).analyse();
+        assert!(analysis.exact);
+        assert_eq!(analysis.safe, vec![(2, 0), (0, 1)]);
+        assert!(analysis.mines.is_empty());
+        assert!(close(probability(&analysis, (1, 0)), 0.5));
+        assert!(close(probability(&analysis, (1, 1)), 0.5));
+        assert!(analysis.interior.is_empty());
+    }
+
+    #[test]
+    fn interior_cells_take_the_mines_the_frontier_leaves() {
+        // one mine in the middle leaves one for two interior cells, two ways,
+        // mines on both ends leave none, one way
+        let analysis = solver(&["#1#1###"], 2).analyse();
+        assert!(close(probability(&analysis, (0, 0)), 1.0 / 3.0));
+        assert!(close(probability(&analysis, (2, 0)), 2.0 / 3.0));
+        assert!(close(probability(&analysis, (4, 0)), 1.0 / 3.0));
+        assert_eq!(analysis.interior, vec![(5, 0), (6, 0)]);
+        assert!(close(analysis.interior_probability.unwrap(), 1.0 / 3.0));
+    }
+
+    #[test]
+    fn nothing_to_go_on() {
+        let view = solver(&["###", "###", "###"], 3);
+        let deductions = view.deduce();
+        assert!(deductions.safe.is_empty() && deductions.mines.is_empty());
+        let analysis = view.analyse();
+        assert!(analysis.probabilities.is_empty());
+        assert_eq!(analysis.interior.len(), 9);
+        assert!(close(analysis.interior_probability.unwrap(), 1.0 / 3.0));
+    }
+
+    #[test]
+    fn enumeration_gives_up_past_its_budget() {
+        let analysis = solver(&["#1#1###"], 2).analyse_within(3);
+        assert!(!analysis.exact);
+        assert!(close(probability(&analysis, (2, 0)), 0.5));
+        assert!(close(analysis.interior_probability.unwrap(), 0.25));
+
+        // a long row of 1s two apart between hidden rows, no number decides anything and the placements multiply
+        let hidden = "#".repeat(60);
+        let numbers = "1#".repeat(30);
+        let view = solver(&[&hidden, &numbers, &hidden], 20);
+        let (safe, mine) = view.propagate();
+        let constraints = view.constraints(&safe, &mine);
+        let (cells, constraint_indices) = Solver::components(&constraints).remove(0);
+        let component_constraints: Vec<&Constraint> =
+            constraint_indices.iter().map(|&index| &constraints[index]).collect();
+        let mut budget = ENUMERATION_BUDGET;
+        assert!(Solver::enumerate(&cells, &component_constraints, 20, &mut budget).is_none());
+        let analysis = view.analyse();
+        assert!(!analysis.exact);
+        assert!(!analysis.probabilities.is_empty());
+        assert!(analysis.probabilities.iter().all(|(_, probability)| (0.0..=1.0).contains(probability)));
+    }
+}
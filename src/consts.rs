@@ -1,9 +1,13 @@
 use ggez::graphics::Color;
 
 pub const BUTTON_SIZE: (f32, f32) = (400.0, 100.0);
-pub const SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 6.0 * BUTTON_SIZE.1);
-pub const SETTINGS_SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 4.0 * BUTTON_SIZE.1);
+pub const SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 7.2 * BUTTON_SIZE.1);
+pub const SETTINGS_SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 6.0 * BUTTON_SIZE.1);
+pub const CUSTOM_SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 4.0 * BUTTON_SIZE.1);
+pub const CONTROLS_SCREEN_SIZE: (f32, f32) = (1.3 * BUTTON_SIZE.0, 5.5 * BUTTON_SIZE.1);
 pub const SCORES_SCREEN_SIZE: (f32, f32) = (15.0 * BUTTON_SIZE.1, 5.0 * BUTTON_SIZE.1);
+pub const HISTORY_SCREEN_SIZE: (f32, f32) = (15.0 * BUTTON_SIZE.1, 6.5 * BUTTON_SIZE.1);
+pub const LEADERBOARD_SCREEN_SIZE: (f32, f32) = (10.0 * BUTTON_SIZE.1, 6.5 * BUTTON_SIZE.1);
 pub const QUAD_SIZE: (f32, f32) = (BUTTON_SIZE.1 * 0.5, BUTTON_SIZE.1 * 0.5);
 // pub const TOP_MARGIN: f32 = 0.1 * SCREEN_SIZE.1;
 
@@ -1,11 +1,106 @@
 mod consts;
 mod minezweeper;
+use clap::{Parser, Subcommand};
 use ggez::{event, graphics::FontData, ContextBuilder};
-use minezweeper::Minezweeper;
+use minezweeper::{cli, store, transfer, tui, Launch, Level, Minezweeper};
+use std::error::Error;
 use std::env;
 use std::path;
+use std::process;
+
+#[derive(Parser)]
+#[command(name = "minezweeper", about = "A modern dark mode minesweeper", version)]
+struct Args {
+    #[arg(long, global = true, value_name = "PATH", help = "Read the config from this file")]
+    config: Option<path::PathBuf>,
+    #[arg(long, global = true, help = "Play in the terminal instead of a window")]
+    tui: bool,
+    // the menu opens when no command is given
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Play, straight into a game when a level, a size or a seed is given")]
+    Play {
+        #[arg(long, value_parser = cli::standard_level, help = "easy, medium or hard")]
+        level: Option<Level>,
+        #[arg(long, value_parser = cli::custom_level, conflicts_with = "level", value_name = "WxH:M",
+            help = "A custom board, width by height with M mines")]
+        custom: Option<Level>,
+        #[arg(long, help = "The seed of the board, easy when no level is given")]
+        seed: Option<u64>,
+    },
+    #[command(about = "List the games played, numbered from 1 in the order they were saved, with a summary per level")]
+    Scores {
+        #[arg(long, help = "easy, medium, hard or WxH:M")]
+        level: Option<Level>,
+        #[arg(long, value_enum, default_value_t = cli::Format::Text)]
+        format: cli::Format,
+    },
+    #[command(about = "Write every score to a .csv or .json file")]
+    Export { file: path::PathBuf },
+    #[command(about = "Add the scores of a .csv or .json file, skipping those already kept")]
+    Import { file: path::PathBuf },
+    #[command(about = "Watch a game again, by the number `scores` lists it under")]
+    Replay { id: usize },
+    #[command(about = "Let the solver play boards on its own and report how it did")]
+    BenchSolver {
+        #[arg(long, default_value = "easy", help = "easy, medium, hard or WxH:M")]
+        level: Level,
+        #[arg(long, default_value_t = 100)]
+        games: usize,
+        #[arg(long, default_value_t = 0, help = "The seed of the first board, the next ones count up")]
+        seed: u64,
+    },
+}
+
+fn run(command: Option<Command>, tui: bool) -> Result<(), Box<dyn Error>> {
+    let launch = match command {
+        None => Launch::Menu,
+        Some(Command::Play { level, custom, seed }) => match (level.or(custom), seed) {
+            (None, None) => Launch::Menu,
+            (level, seed) => Launch::Game(level.unwrap_or(Level::Easy), seed),
+        },
+        Some(Command::Replay { id }) => Launch::Replay(cli::replay(&*store::open(), id)?),
+        Some(Command::Scores { level, format }) => {
+            println!("{}", cli::scores(&*store::open(), level, format)?);
+            return Ok(());
+        }
+        Some(Command::Export { file }) => {
+            let count = transfer::export(&*store::open(), &file)?;
+            println!("Exported {} scores to {}", count, file.display());
+            return Ok(());
+        }
+        Some(Command::Import { file }) => {
+            println!("{}", transfer::import(&mut *store::open(), &file)?);
+            return Ok(());
+        }
+        Some(Command::BenchSolver { level, games, seed }) => {
+            println!("{}", cli::bench_solver(level, games, seed)?);
+            return Ok(());
+        }
+    };
+    if tui {
+        tui::run(launch)
+    } else {
+        play(launch)
+    }
+}
 
 fn main() {
+    let args = Args::parse();
+    let result = minezweeper::config::load(args.config)
+        .map_err(|err| err.into())
+        .and_then(|_| run(args.command, args.tui));
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn play(launch: Launch) -> Result<(), Box<dyn Error>> {
     // fetching resource directory
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
@@ -27,7 +122,7 @@ fn main() {
 
     let font = FontData::from_path(&ctx.fs, "/Synemono-Regular.ttf").expect("Could not load font");
     ctx.gfx.add_font("SyneMono", font);
-    let minezweeper = Minezweeper::new(&mut ctx);
+    let minezweeper = Minezweeper::new(&mut ctx, launch)?;
 
-    event::run(ctx, event_loop, minezweeper);
+    event::run(ctx, event_loop, minezweeper)
 }
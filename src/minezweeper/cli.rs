@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::time::Instant;
+
+use clap::ValueEnum;
+use minezweeper_engine as engine;
+
+use crate::minezweeper::{
+    config,
+    game::{replay::Replay, GameState},
+    menu::scores::Statistic,
+    settings::Score,
+    store::ScoreStore,
+    Level,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+// only the levels of the menu, custom sizes have their own option
+pub fn standard_level(text: &str) -> Result<Level, String> {
+    match text.parse()? {
+        Level::Custom { .. } => Err(format!("`{}` is not a level, use easy, medium or hard", text)),
+        level => Ok(level),
+    }
+}
+
+pub fn custom_level(text: &str) -> Result<Level, String> {
+    match text.parse()? {
+        level @ Level::Custom { .. } => Ok(level),
+        _ => Err(format!("`{}` is not a custom size, use WxH:M", text)),
+    }
+}
+
+// games are numbered from 1 in the order they were saved, new games only ever add numbers
+fn numbered(store: &dyn ScoreStore) -> Result<Vec<(usize, Score)>, Box<dyn Error>> {
+    Ok((1..).zip(store.all()?).collect())
+}
+
+fn optional(value: Option<f32>) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.2}", value))
+}
+
+pub fn scores(store: &dyn ScoreStore, level: Option<Level>, format: Format) -> Result<String, Box<dyn Error>> {
+    let scores: Vec<(usize, Score)> = numbered(store)?
+        .into_iter()
+        .filter(|(_, score)| level.is_none_or(|level| score.level == level))
+        .collect();
+    if format == Format::Json {
+        let mut records = Vec::new();
+        for (id, score) in scores.iter() {
+            let mut record = serde_json::to_value(score.to_record())?;
+            record["id"] = (*id).into();
+            records.push(record);
+        }
+        return Ok(serde_json::to_string_pretty(&records)?);
+    }
+
+    let mut lines = vec![format!(
+        "{:>5}  {:<16}  {:<10}  {:<9}  {:>8}  {:>6}  {}",
+        "id", "date", "level", "state", "time", "3BV/s", "profile"
+    )];
+    for (id, score) in scores.iter() {
+        lines.push(format!(
+            "{:>5}  {:<16}  {:<10}  {:<9}  {:>8.2}  {:>6}  {}",
+            id,
+            score.date_time.format("%Y-%m-%d %H:%M").to_string(),
+            score.level.label(),
+            score.game_state.to_string(),
+            score.time,
+            optional(score.bbbv_per_second),
+            score.profile
+        ));
+    }
+    // a summary per level, in the order they were first played
+    let mut levels: Vec<Level> = Vec::new();
+    for (_, score) in scores.iter() {
+        if !levels.contains(&score.level) {
+            levels.push(score.level);
+        }
+    }
+    lines.push(String::new());
+    for level in levels {
+        let stat = Statistic::from_scores(scores.iter().map(|(_, score)| score).filter(|score| score.level == level));
+        lines.push(format!(
+            "{}: {} played, {} won ({:.1}%), best {}, average {}",
+            level.label(),
+            stat.played,
+            stat.won,
+            stat.win_rate().unwrap_or_default(),
+            optional(stat.best_time),
+            optional(stat.average_time)
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn replay(store: &dyn ScoreStore, id: usize) -> Result<Replay, Box<dyn Error>> {
+    let (_, score) = numbered(store)?
+        .into_iter()
+        .find(|(number, _)| *number == id)
+        .ok_or(format!("There is no game {}, `scores` lists them", id))?;
+    score
+        .replay
+        .ok_or_else(|| format!("Game {} was not recorded, it can not be replayed", id).into())
+}
+
+// plays the board out with the solver alone, the least likely mine is cleared when nothing is certain
+fn solve(game: &mut engine::Game) -> usize {
+    let (width, height) = game.shape();
+    let mut guesses = 0;
+    game.clear(width / 2, height / 2);
+    while game.state() == GameState::Playing {
+        let analysis = game.solver().analyse();
+        let safe: Vec<(usize, usize)> = analysis
+            .safe
+            .into_iter()
+            .filter(|&(x, y)| !game.cell(x, y).cleared)
+            .collect();
+        if !safe.is_empty() {
+            for (x, y) in safe {
+                game.clear(x, y);
+            }
+            continue;
+        }
+        let guess = analysis
+            .probabilities
+            .into_iter()
+            .chain(analysis.interior.first().copied().zip(analysis.interior_probability))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some(((x, y), _)) = guess else {
+            break;
+        };
+        guesses += 1;
+        game.clear(x, y);
+    }
+    guesses
+}
+
+pub fn bench_solver(level: Level, games: usize, seed: u64) -> Result<String, Box<dyn Error>> {
+    let level_info = level.level_info();
+    let profile = &config::get().profile;
+    let (safe_zone, generation) = (config::safe_zone(profile), config::generation(profile));
+    let (mut won, mut won_without_guessing, mut guesses) = (0, 0, 0);
+    let start = Instant::now();
+    for game in 0..games as u64 {
+        let mut game = engine::Game::new(
+            level_info.grid_size,
+            level_info.number_of_mines,
+            Some(seed.wrapping_add(game)),
+            safe_zone,
+            generation,
+        )?;
+        let guessed = solve(&mut game);
+        if game.state() == GameState::Won {
+            won += 1;
+            if guessed == 0 {
+                won_without_guessing += 1;
+            }
+        }
+        guesses += guessed;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let per_game = |value: f64| if games == 0 { 0.0 } else { value / games as f64 };
+    Ok(format!(
+        "{}, {} games from seed {}, {} {}\n\
+         won {} ({:.1}%), {} without guessing\n\
+         {:.2} guesses and {:.2} ms per game",
+        level.label(),
+        games,
+        seed,
+        safe_zone.label(),
+        generation.label(),
+        won,
+        per_game(100.0 * won as f64),
+        won_without_guessing,
+        per_game(guesses as f64),
+        per_game(1000.0 * elapsed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::store::MemoryStore;
+
+    #[test]
+    fn levels_from_the_command_line() {
+        assert_eq!("Medium".parse::<Level>(), Ok(Level::Medium));
+        assert_eq!("20x10:30".parse::<Level>(), Ok(Level::Custom { width: 20, height: 10, mines: 30 }));
+        assert!("20x10".parse::<Level>().is_err());
+        assert!("3x3:9".parse::<Level>().is_err());
+        assert!(standard_level("9x9:10").is_err());
+        assert!(custom_level("easy").is_err());
+    }
+
+    #[test]
+    fn scores_keep_their_number_when_filtered() {
+        let mut store = MemoryStore::default();
+        store
+            .save_all(&[
+                Score::for_test(Level::Easy, GameState::Won, 10.0),
+                Score::for_test(Level::Hard, GameState::Lost, 50.0),
+                Score::for_test(Level::Easy, GameState::Lost, 3.0),
+            ])
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&scores(&store, Some(Level::Easy), Format::Json).unwrap()).unwrap();
+        let ids: Vec<u64> = json.as_array().unwrap().iter().map(|record| record["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 3]);
+        let text = scores(&store, Some(Level::Easy), Format::Text).unwrap();
+        assert!(text.ends_with("Easy: 2 played, 1 won (50.0%), best 10.00, average 10.00"));
+        assert!(replay(&store, 2).is_err());
+        assert!(replay(&store, 4).is_err());
+    }
+}
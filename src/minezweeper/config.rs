@@ -0,0 +1,483 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use ggez::graphics::Color;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::consts;
+use crate::minezweeper::game::{Game, Generation, SafeZone};
+use crate::minezweeper::settings::Controls;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static CONFIG_PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+const DATABASE_ENV: &str = "MINEZWEEPER_DATABASE";
+
+// always there, scores from before profiles belong to it
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(PathBuf, String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "Could not read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "Invalid config {}: {}", path.display(), err),
+            ConfigError::Invalid(path, err) => write!(f, "Invalid config {}: {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LevelConfig {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Levels {
+    pub easy: LevelConfig,
+    pub medium: LevelConfig,
+    pub hard: LevelConfig,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels {
+            easy: LevelConfig { width: 9, height: 9, mines: 10 },
+            medium: LevelConfig { width: 16, height: 16, mines: 40 },
+            hard: LevelConfig { width: 30, height: 16, mines: 99 },
+        }
+    }
+}
+
+fn parse_color(hex: &str) -> Result<Color, String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    let channel = |i: usize| {
+        digits
+            .get(2 * i..2 * i + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+    match (digits.len(), channel(0), channel(1), channel(2)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color::from_rgb(r, g, b)),
+        (8, Some(r), Some(g), Some(b)) => channel(3)
+            .map(|a| Color::from_rgba(r, g, b, a))
+            .ok_or(format!("invalid color `{}`, expected #rrggbb or #rrggbbaa", hex)),
+        _ => Err(format!("invalid color `{}`, expected #rrggbb or #rrggbbaa", hex)),
+    }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    parse_color(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+fn number_colors<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color; 8], D::Error> {
+    let colors = Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|hex| parse_color(hex))
+        .collect::<Result<Vec<Color>, String>>()
+        .map_err(de::Error::custom)?;
+    colors
+        .try_into()
+        .map_err(|colors: Vec<Color>| de::Error::invalid_length(colors.len(), &"8 colors, one per number"))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    #[serde(deserialize_with = "color")]
+    pub background: Color,
+    #[serde(deserialize_with = "color")]
+    pub button: Color,
+    #[serde(deserialize_with = "color")]
+    pub button_hovered: Color,
+    #[serde(deserialize_with = "color")]
+    pub button_clicked: Color,
+    #[serde(deserialize_with = "color")]
+    pub cleared: Color,
+    #[serde(deserialize_with = "color")]
+    pub cleared_hovered: Color,
+    #[serde(deserialize_with = "color")]
+    pub cleared_clicked: Color,
+    #[serde(deserialize_with = "color")]
+    pub text: Color,
+    #[serde(deserialize_with = "color")]
+    pub flag: Color,
+    #[serde(deserialize_with = "color")]
+    pub question_mark: Color,
+    #[serde(deserialize_with = "color")]
+    pub mine: Color,
+    #[serde(deserialize_with = "number_colors")]
+    pub numbers: [Color; 8],
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            background: Color::BLACK,
+            button: consts::BUTTON_COLOR,
+            button_hovered: consts::BUTTON_HOVERED_COLOR,
+            button_clicked: consts::BUTTON_CLICKED_COLOR,
+            cleared: consts::BUTTON_CLEARED_COLOR,
+            cleared_hovered: consts::BUTTON_CLEARED_HOVERED_COLOR,
+            cleared_clicked: consts::BUTTON_CLEARED_CLICKED_COLOR,
+            text: consts::BUTTON_TEXT_COLOR,
+            flag: consts::FLAG_COLOR,
+            question_mark: consts::QUESTION_MARK_COLOR,
+            mine: consts::MINE_COLOR,
+            numbers: consts::NUMBER_COLORS,
+        }
+    }
+}
+
+// where scores are kept, memory keeps them until the game is closed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum StoreKind {
+    Sqlite,
+    Csv,
+    Memory,
+}
+
+// what a profile keeps to itself, anything left out is taken from the top of the config
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub controls: Option<Controls>,
+    pub safe_zone: Option<SafeZone>,
+    pub generation: Option<Generation>,
+}
+
+// every key is optional, whatever is left out keeps its default
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub levels: Levels,
+    pub colors: Colors,
+    pub controls: Controls,
+    pub store: StoreKind,
+    // the name written with every score, defaults to the user name
+    pub player: Option<String>,
+    // defaults to scores.db, or scores.csv for the csv store, in the data directory
+    pub database: Option<PathBuf>,
+    pub safe_zone: SafeZone,
+    pub generation: Generation,
+    // the profile played at start, the last one picked in the menu
+    pub profile: String,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            levels: Levels::default(),
+            colors: Colors::default(),
+            controls: Controls::default(),
+            store: StoreKind::Sqlite,
+            player: None,
+            database: None,
+            safe_zone: SafeZone::Opening,
+            generation: Generation::Classic,
+            profile: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    fn validate(&self) -> Result<(), String> {
+        for (name, level) in [
+            ("easy", &self.levels.easy),
+            ("medium", &self.levels.medium),
+            ("hard", &self.levels.hard),
+        ] {
+            Game::validate((level.width, level.height), level.mines)
+                .map_err(|err| format!("levels.{}: {}", name, err))?;
+        }
+        if self.safe_zone == SafeZone::None {
+            return Err("safe_zone: None is only used for old scores".to_string());
+        }
+        for (name, profile) in self.profiles.iter() {
+            if name.is_empty() {
+                return Err("profiles: a profile needs a name".to_string());
+            }
+            if profile.safe_zone == Some(SafeZone::None) {
+                return Err(format!("profiles.{}.safe_zone: None is only used for old scores", name));
+            }
+        }
+        if !self.profile_names().contains(&self.profile) {
+            return Err(format!("profile: no profile named `{}`", self.profile));
+        }
+        Ok(())
+    }
+
+    fn profile_names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_PROFILE.to_string())
+            .chain(self.profiles.keys().filter(|name| *name != DEFAULT_PROFILE).cloned())
+            .collect()
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("minezweeper").join("config.toml"))
+}
+
+// a missing file is only an error when its path was given explicitly
+pub fn load(path: Option<PathBuf>) -> Result<(), ConfigError> {
+    let explicit = path.is_some();
+    let path = path.or_else(default_path);
+    let config = match &path {
+        Some(path) if explicit || path.exists() => {
+            let text = fs::read_to_string(path).map_err(|err| ConfigError::Read(path.clone(), err))?;
+            let config: Config = toml::from_str(&text).map_err(|err| ConfigError::Parse(path.clone(), err))?;
+            config
+                .validate()
+                .map_err(|err| ConfigError::Invalid(path.clone(), err))?;
+            config
+        }
+        _ => Config::default(),
+    };
+    let _ = CONFIG.set(config);
+    let _ = CONFIG_PATH.set(path);
+    Ok(())
+}
+
+// the environment variable wins over the config file
+pub fn database_path() -> PathBuf {
+    let file_name = match get().store {
+        StoreKind::Csv => "scores.csv",
+        StoreKind::Sqlite | StoreKind::Memory => "scores.db",
+    };
+    std::env::var_os(DATABASE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| get().database.clone())
+        .or_else(|| dirs::data_dir().map(|dir| dir.join("minezweeper").join(file_name)))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+// kept next to the scores, wherever they are, one game per profile
+pub fn saved_game_path(profile: &str) -> PathBuf {
+    let file_name = match profile {
+        DEFAULT_PROFILE => "saved_game.json".to_string(),
+        profile => format!(
+            "saved_game.{}.json",
+            profile.replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
+        ),
+    };
+    database_path().with_file_name(file_name)
+}
+
+// players sharing the user name are told apart by their profile
+pub fn player_name(profile: &str) -> String {
+    if profile != DEFAULT_PROFILE {
+        return profile.to_string();
+    }
+    get()
+        .player
+        .clone()
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "player".to_string())
+}
+
+// the default profile first, then the ones declared as [profiles.<name>]
+pub fn profile_names() -> Vec<String> {
+    get().profile_names()
+}
+
+pub fn controls(profile: &str) -> Controls {
+    get()
+        .profiles
+        .get(profile)
+        .and_then(|profile| profile.controls.clone())
+        .unwrap_or_else(|| get().controls.clone())
+}
+
+pub fn safe_zone(profile: &str) -> SafeZone {
+    get()
+        .profiles
+        .get(profile)
+        .and_then(|profile| profile.safe_zone)
+        .unwrap_or(get().safe_zone)
+}
+
+pub fn generation(profile: &str) -> Generation {
+    get()
+        .profiles
+        .get(profile)
+        .and_then(|profile| profile.generation)
+        .unwrap_or(get().generation)
+}
+
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+pub fn colors() -> &'static Colors {
+    &get().colors
+}
+
+// rewrites part of the config file, everything else in it is kept as it was written, comments included
+fn edit(change: impl FnOnce(&mut DocumentMut) -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let path = CONFIG_PATH
+        .get()
+        .cloned()
+        .flatten()
+        .ok_or("No config directory on this platform")?;
+    let mut document: DocumentMut = match path.exists() {
+        true => fs::read_to_string(&path)?.parse()?,
+        false => DocumentMut::new(),
+    };
+    change(&mut document)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, document.to_string())?;
+    Ok(())
+}
+
+// the table of a profile, added as [profiles.<name>] when it is not there yet
+fn profile_table<'a>(document: &'a mut DocumentMut, profile: &str) -> Result<&'a mut Table, Box<dyn Error>> {
+    let profiles = document
+        .entry("profiles")
+        .or_insert_with(|| {
+            let mut profiles = Table::new();
+            profiles.set_implicit(true);
+            Item::Table(profiles)
+        })
+        .as_table_mut()
+        .ok_or("profiles is not a table")?;
+    profiles
+        .entry(profile)
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .ok_or_else(|| format!("profiles.{} is not a table", profile).into())
+}
+
+// every profile keeps its own table, the top [controls] stay what the other profiles inherit
+pub fn save_controls(profile: &str, controls: &Controls) -> Result<(), Box<dyn Error>> {
+    edit(|document| set_profile_controls(document, profile, controls))
+}
+
+fn set_profile_controls(document: &mut DocumentMut, profile: &str, controls: &Controls) -> Result<(), Box<dyn Error>> {
+    let controls: DocumentMut = toml::to_string(controls)?.parse()?;
+    profile_table(document, profile)?.insert("controls", Item::Table(controls.as_table().clone()));
+    Ok(())
+}
+
+// the first click and the generation picked in the menu, kept by the profile they were picked for
+pub fn save_preferences(profile: &str, safe_zone: SafeZone, generation: Generation) -> Result<(), Box<dyn Error>> {
+    edit(|document| set_preferences(document, profile, safe_zone, generation))
+}
+
+fn set_preferences(
+    document: &mut DocumentMut,
+    profile: &str,
+    safe_zone: SafeZone,
+    generation: Generation,
+) -> Result<(), Box<dyn Error>> {
+    let (safe_zone, generation) = (name(&safe_zone)?, name(&generation)?);
+    let table = profile_table(document, profile)?;
+    set_value(table, "safe_zone", safe_zone);
+    set_value(table, "generation", generation);
+    Ok(())
+}
+
+// an option as it is written in the config
+fn name(value: &impl Serialize) -> Result<String, Box<dyn Error>> {
+    match toml::Value::try_from(value)? {
+        toml::Value::String(name) => Ok(name),
+        other => Err(format!("{} can not be written as a name", other).into()),
+    }
+}
+
+// a value written over in place keeps the comments around it
+fn set_value(table: &mut Table, key: &str, value: impl Into<Value>) {
+    let mut value = value.into();
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+pub fn save_profile(profile: &str) -> Result<(), Box<dyn Error>> {
+    edit(|document| {
+        set_value(document.as_table_mut(), "profile", profile);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::settings::Action;
+    use ggez::input::keyboard::KeyCode;
+
+    #[test]
+    fn the_default_profile_does_not_rebind_the_others() {
+        let mut document: DocumentMut = "[controls]\nflag = [\"F\"]\n\n[profiles.alice]\n".parse().unwrap();
+        let mut controls = Controls::default();
+        controls.unbind(Action::Flag);
+        controls.bind(Action::Flag, KeyCode::G).unwrap();
+        set_profile_controls(&mut document, DEFAULT_PROFILE, &controls).unwrap();
+
+        let config: Config = toml::from_str(&document.to_string()).unwrap();
+        assert_eq!(config.controls.keys(Action::Flag), &[KeyCode::F]);
+        assert!(config.profiles["alice"].controls.is_none());
+        let own = config.profiles[DEFAULT_PROFILE].controls.as_ref().unwrap();
+        assert_eq!(own.keys(Action::Flag), &[KeyCode::G]);
+    }
+
+    #[test]
+    fn preferences_are_kept_by_their_profile() {
+        let mut document: DocumentMut = "safe_zone = \"Opening\"\n\n[profiles.alice]\nsafe_zone = \"Cell\" # careful\n"
+            .parse()
+            .unwrap();
+        set_preferences(&mut document, "alice", SafeZone::MoveMine, Generation::NoGuess).unwrap();
+        set_preferences(&mut document, "bob", SafeZone::Cell, Generation::Classic).unwrap();
+
+        let edited = document.to_string();
+        assert!(edited.contains("safe_zone = \"MoveMine\" # careful\n"));
+        let config: Config = toml::from_str(&edited).unwrap();
+        assert_eq!(config.safe_zone, SafeZone::Opening);
+        assert_eq!(config.profiles["alice"].safe_zone, Some(SafeZone::MoveMine));
+        assert_eq!(config.profiles["alice"].generation, Some(Generation::NoGuess));
+        assert_eq!(config.profiles["bob"].safe_zone, Some(SafeZone::Cell));
+    }
+
+    #[test]
+    fn edits_keep_the_comments_and_the_layout() {
+        let text = "# picked in the menu\nprofile = \"default\" # or alice\n\n[levels.easy] # small\nwidth = 8\nheight = 8\nmines = 10\n";
+        let mut document: DocumentMut = text.parse().unwrap();
+        set_value(document.as_table_mut(), "profile", "alice");
+        set_profile_controls(&mut document, "alice", &Controls::default()).unwrap();
+
+        let edited = document.to_string();
+        assert!(edited.starts_with("# picked in the menu\nprofile = \"alice\" # or alice\n\n[levels.easy] # small\nwidth = 8\n"));
+        assert!(edited.contains("[profiles.alice.controls]"));
+        assert!(!edited.contains("[profiles]\n"));
+    }
+}
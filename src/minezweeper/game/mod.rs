@@ -1,365 +1,656 @@
-mod grid;
-use std::fmt::Display;
-use std::time::Instant;
+pub mod replay;
+mod stopwatch;
 
 use ggez::{
-    graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout},
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout},
+    input::mouse::MouseButton,
     mint::Point2,
     Context, GameResult,
 };
-use grid::Grid;
+use minezweeper_engine as engine;
+pub use minezweeper_engine::{Clicks, GameState, Generation, GridError, SafeZone};
+use minezweeper_engine::{Grid, Move, Replay, Step};
+use serde::{Deserialize, Serialize};
+use stopwatch::Stopwatch;
 
 use crate::{
     consts,
     minezweeper::settings::{Action, Direction},
 };
 
-use crate::minezweeper::draw_text;
+use crate::minezweeper::{config::colors, draw_text};
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum GameState {
-    Won,
-    Lost,
-    Playing,
-    Abandoned,
+// a cell to play next, with its mine probability when no cell is safe for sure
+struct Hint {
+    cell: (usize, usize),
+    probability: Option<f64>,
 }
 
-impl Display for GameState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GameState::Won => write!(f, "Won"),
-            GameState::Lost => write!(f, "Lost"),
-            GameState::Playing => write!(f, "Playing"),
-            GameState::Abandoned => write!(f, "Abandoned"),
-        }
-    }
+// what a cell shows, the window and the terminal each draw it their own way
+pub enum Face {
+    Blank,
+    Number(usize),
+    Mine,
+    Flag,
+    QuestionMark,
 }
 
-pub struct Game {
+// what is kept of a game in progress, the timer picks up from elapsed
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
     grid: Grid,
+    elapsed: f32,
+    hints_used: usize,
+    #[serde(default)]
+    first_click: Option<f32>,
+    #[serde(default)]
+    steps: Vec<Step>,
+    #[serde(default)]
+    clicks: Clicks,
+}
+
+// the rules are left to the engine, this keeps the time, the cursor and what is drawn
+pub struct Game {
+    rules: engine::Game,
     last_hovered_cell: Option<(usize, usize)>,
-    game_state: GameState,
-    time: Instant,
+    // the cell a mouse button is held down on
+    pressed_cell: Option<(usize, usize)>,
+    time: Stopwatch,
     final_time: f32,
+    hint: Option<Hint>,
+    hints_used: usize,
+    quad_size: (f32, f32),
+    // elapsed time at the first click, replays count from there
+    first_click: Option<f32>,
+    steps: Vec<Step>,
+    // shown instead of the seed, while replaying
+    caption: Option<String>,
+    // shown across the board once the game is over
+    banner: Option<String>,
+    // stopped by a paused replay, unlike a pause the board stays in view
+    held: bool,
 }
 
 impl Game {
-    fn cell_rect(x: usize, y: usize) -> Rect {
+    fn cell_rect(&self, x: usize, y: usize) -> Rect {
         Rect::new(
-            x as f32 * consts::QUAD_SIZE.0 + 0.1 * consts::QUAD_SIZE.0,
-            consts::QUAD_SIZE.1 + y as f32 * consts::QUAD_SIZE.1 + 0.1 * consts::QUAD_SIZE.1,
-            consts::QUAD_SIZE.0 - 0.2 * consts::QUAD_SIZE.0,
-            consts::QUAD_SIZE.1 - 0.2 * consts::QUAD_SIZE.1,
+            x as f32 * self.quad_size.0 + 0.1 * self.quad_size.0,
+            self.quad_size.1 + y as f32 * self.quad_size.1 + 0.1 * self.quad_size.1,
+            self.quad_size.0 - 0.2 * self.quad_size.0,
+            self.quad_size.1 - 0.2 * self.quad_size.1,
         )
     }
 
-    fn cell_position(x_pos: f32, y_pos: f32) -> Option<(usize, usize)> {
-        if y_pos - consts::QUAD_SIZE.1 < 0.0 {
-            None
-        } else {
-            Some((
-                (x_pos / consts::QUAD_SIZE.0) as usize,
-                ((y_pos - consts::QUAD_SIZE.1) / consts::QUAD_SIZE.1) as usize,
-            ))
+    fn cell_position(&self, x_pos: f32, y_pos: f32) -> Option<(usize, usize)> {
+        let (width, height) = self.rules.shape();
+        if y_pos - self.quad_size.1 < 0.0 || x_pos < 0.0 {
+            return None;
+        }
+        let (x, y) = (
+            (x_pos / self.quad_size.0) as usize,
+            ((y_pos - self.quad_size.1) / self.quad_size.1) as usize,
+        );
+        (x < width && y < height).then_some((x, y))
+    }
+
+    pub fn new(
+        shape: (usize, usize),
+        number_of_mines: usize,
+        seed: Option<u64>,
+        safe_zone: SafeZone,
+        generation: Generation,
+    ) -> Result<Self, GridError> {
+        Ok(Self::with_rules(engine::Game::new(shape, number_of_mines, seed, safe_zone, generation)?))
+    }
+
+    fn with_rules(rules: engine::Game) -> Self {
+        Game {
+            rules,
+            last_hovered_cell: None,
+            pressed_cell: None,
+            time: Stopwatch::start(),
+            final_time: 0.0,
+            hint: None,
+            hints_used: 0,
+            quad_size: consts::QUAD_SIZE,
+            first_click: None,
+            steps: Vec::new(),
+            caption: None,
+            banner: None,
+            held: false,
         }
     }
 
-    pub fn new(shape: (usize, usize), number_of_mines: usize) -> Self {
+    fn from_replay(replay: &Replay) -> Self {
+        Self::with_rules(engine::Game::from_replay(replay))
+    }
+
+    pub fn restore(snapshot: Snapshot) -> Self {
+        let mut time = Stopwatch::start();
+        time.set_elapsed(snapshot.elapsed);
         Game {
-            grid: Grid::new(shape, number_of_mines),
+            rules: engine::Game::resume(snapshot.grid, snapshot.clicks),
             last_hovered_cell: None,
-            game_state: GameState::Playing,
-            time: Instant::now(),
-            final_time: 0.0
+            pressed_cell: None,
+            time,
+            final_time: 0.0,
+            hint: None,
+            hints_used: snapshot.hints_used,
+            quad_size: consts::QUAD_SIZE,
+            first_click: snapshot.first_click,
+            steps: snapshot.steps,
+            caption: None,
+            banner: None,
+            held: false,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.rules.grid().clone(),
+            elapsed: self.time.elapsed(),
+            hints_used: self.hints_used,
+            first_click: self.first_click,
+            steps: self.steps.clone(),
+            clicks: self.rules.clicks(),
+        }
+    }
+
+    // boards that were never clicked have nothing to replay
+    pub fn replay(&self) -> Option<Replay> {
+        self.rules.replay(self.steps.clone())
+    }
+
+    fn set_caption(&mut self, caption: String) {
+        self.caption = Some(caption);
+    }
+
+    pub fn set_banner(&mut self, banner: String) {
+        self.banner = Some(banner);
+    }
+
+    fn set_elapsed(&mut self, elapsed: f32) {
+        self.time.set_elapsed(elapsed);
+    }
+
+    fn hold_clock(&mut self, held: bool) {
+        self.held = held;
+        if held {
+            self.time.pause();
+        } else {
+            self.time.resume();
+        }
+    }
+
+    pub fn validate(shape: (usize, usize), number_of_mines: usize) -> Result<(), GridError> {
+        engine::Game::validate(shape, number_of_mines)
+    }
+
+    // large boards are drawn with smaller cells so they fit on the screen
+    pub fn set_quad_size(&mut self, quad_size: (f32, f32)) {
+        self.quad_size = quad_size;
+    }
+
+    // the text above the board, the outcome once the game is over
+    pub fn status(&self) -> String {
+        match (self.rules.state(), &self.caption, self.rules.generation()) {
+            (GameState::Lost, _, _) => "LOST".to_string(),
+            (GameState::Won, _, _) => "WON".to_string(),
+            (GameState::Abandoned, _, _) => String::new(),
+            _ if self.is_paused() => "PAUSED".to_string(),
+            (_, Some(caption), _) => caption.clone(),
+            (_, None, Generation::Classic) => format!("#{}", self.rules.seed()),
+            // no board without guessing was found, the player is told rather than misled
+            (_, None, Generation::NoGuess) if self.rules.fell_back() => format!("#{} NG failed", self.rules.seed()),
+            (_, None, Generation::NoGuess) => format!("#{} NG", self.rules.seed()),
+        }
+    }
+
+    // seconds on the clock, stopped once the game is over
+    pub fn clock(&self) -> f32 {
+        if self.rules.state() == GameState::Playing {
+            self.time.elapsed()
+        } else {
+            self.final_time
+        }
+    }
+
+    pub fn cell_color(&self, x: usize, y: usize) -> Color {
+        let hovered = self.last_hovered_cell == Some((x, y));
+        let clicked = self.pressed_cell == Some((x, y));
+        match (hovered, clicked, self.rules.cell(x, y).cleared) {
+            _ if self.is_paused() => colors().button,
+            (true, true, true) => colors().cleared_clicked,
+            (true, true, false) => colors().button_clicked,
+            (true, false, true) => colors().cleared_hovered,
+            (true, false, false) => colors().button_hovered,
+            (false, _, true) => colors().cleared,
+            (false, _, false) => colors().button,
+        }
+    }
+
+    // nothing of the board shows through while paused
+    pub fn face(&self, x: usize, y: usize) -> Face {
+        let cell = self.rules.cell(x, y);
+        match cell.get_value() {
+            _ if self.is_paused() => Face::Blank,
+            -1 if cell.cleared => Face::Mine,
+            value if cell.cleared && value > 0 => Face::Number(value as usize),
+            _ if cell.cleared => Face::Blank,
+            _ if cell.flagged => Face::Flag,
+            _ if cell.question_marked => Face::QuestionMark,
+            _ => Face::Blank,
         }
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        let (grid_x, grid_y) = self.grid.get_shape();
+        let (grid_x, grid_y) = self.rules.shape();
 
         draw_text(
             canvas,
-            self.grid
-                .get_number_of_remaining_mines()
-                .to_string()
-                .as_str(),
+            self.rules.remaining_mines().to_string().as_str(),
             (
-                (self.grid.get_shape().0 as f32 - 0.1) * consts::QUAD_SIZE.1,
-                0.5 * consts::QUAD_SIZE.1,
+                (grid_x as f32 - 0.1) * self.quad_size.1,
+                0.5 * self.quad_size.1,
             ),
-            0.9 * consts::QUAD_SIZE.1,
+            0.9 * self.quad_size.1,
             TextLayout {
                 h_align: TextAlign::End,
                 v_align: TextAlign::Middle,
             },
-            consts::FLAG_COLOR,
+            colors().flag,
         )?;
 
-        if self.game_state != GameState::Playing {
+        if self.rules.state() != GameState::Playing {
             draw_text(
                 canvas,
-                match self.game_state {
-                    GameState::Lost => "LOST",
-                    GameState::Won => "WON",
-                    _ => "",
-                },
+                self.status().as_str(),
                 (
-                    (grid_x as f32) * consts::QUAD_SIZE.0 * 0.5,
-                    consts::QUAD_SIZE.1 as f32 * 0.5,
+                    (grid_x as f32) * self.quad_size.0 * 0.5,
+                    self.quad_size.1 * 0.5,
                 ),
-                0.9 * consts::QUAD_SIZE.1,
+                0.9 * self.quad_size.1,
                 TextLayout::center(),
-                consts::FLAG_COLOR,
+                colors().flag,
             )?;
             draw_text(
                 canvas,
-                format!("{:.0}", self.final_time).as_str(),
-                (0.1 * consts::QUAD_SIZE.1, 0.5 * consts::QUAD_SIZE.1),
-                0.9 * consts::QUAD_SIZE.1,
+                format!("{:.0}", self.clock()).as_str(),
+                (0.1 * self.quad_size.1, 0.5 * self.quad_size.1),
+                0.9 * self.quad_size.1,
                 TextLayout {
                     h_align: TextAlign::Begin,
                     v_align: TextAlign::Middle,
                 },
-                consts::FLAG_COLOR,
+                colors().flag,
             )?;
         } else {
             draw_text(
                 canvas,
-                format!("{:.0}", Instant::now().duration_since(self.time).as_secs_f32()).as_str(),
-                (0.1 * consts::QUAD_SIZE.1, 0.5 * consts::QUAD_SIZE.1),
-                0.9 * consts::QUAD_SIZE.1,
+                format!("{:.0}", self.clock()).as_str(),
+                (0.1 * self.quad_size.1, 0.5 * self.quad_size.1),
+                0.9 * self.quad_size.1,
                 TextLayout {
                     h_align: TextAlign::Begin,
                     v_align: TextAlign::Middle,
                 },
-                consts::FLAG_COLOR,
+                colors().flag,
+            )?;
+            draw_text(
+                canvas,
+                self.status().as_str(),
+                (
+                    (grid_x as f32) * self.quad_size.0 * 0.5,
+                    self.quad_size.1 * 0.5,
+                ),
+                0.4 * self.quad_size.1,
+                TextLayout::center(),
+                colors().text,
             )?;
         }
 
         for x in 0..grid_x {
             for y in 0..grid_y {
-                let cell = self.grid.get(x, y);
-                let rect = Self::cell_rect(x, y);
-                let button_color = match (cell.hovered, cell.clicked, cell.cleared) {
-                    (true, true, true) => consts::BUTTON_CLEARED_CLICKED_COLOR,
-                    (true, true, false) => consts::BUTTON_CLICKED_COLOR,
-                    (true, false, true) => consts::BUTTON_CLEARED_HOVERED_COLOR,
-                    (true, false, false) => consts::BUTTON_HOVERED_COLOR,
-                    (false, _, true) => consts::BUTTON_CLEARED_COLOR,
-                    (false, _, false) => consts::BUTTON_COLOR,
-                };
+                let rect = self.cell_rect(x, y);
                 let rectangle = Mesh::new_rounded_rectangle(
                     ctx,
                     DrawMode::fill(),
                     rect,
-                    0.2 * consts::QUAD_SIZE.0,
-                    button_color,
+                    0.2 * self.quad_size.0,
+                    self.cell_color(x, y),
                 )?;
                 canvas.draw(&rectangle, DrawParam::default());
 
-                if cell.cleared {
-                    let value = cell.get_value();
-                    if value > 0 {
-                        let color = consts::NUMBER_COLORS[(value - 1) as usize];
+                match self.face(x, y) {
+                    Face::Number(value) => {
+                        let color = colors().numbers[value - 1];
 
                         draw_text(
                             canvas,
                             value.to_string().as_str(),
                             (
-                                rect.left() + 0.4 * consts::QUAD_SIZE.1,
-                                rect.top() + 0.4 * consts::QUAD_SIZE.1,
+                                rect.left() + 0.4 * self.quad_size.1,
+                                rect.top() + 0.4 * self.quad_size.1,
                             ),
-                            0.8 * consts::QUAD_SIZE.1,
+                            0.8 * self.quad_size.1,
                             TextLayout::center(),
                             color,
                         )?;
-                    } else if value == -1 {
+                    }
+                    Face::Mine => {
                         // Draw a mine
                         let circle = Mesh::new_circle(
                             ctx,
                             DrawMode::fill(),
                             rect.center(),
-                            0.2 * consts::QUAD_SIZE.1,
+                            0.2 * self.quad_size.1,
                             1.0,
-                            consts::MINE_COLOR,
+                            colors().mine,
                         )?;
                         canvas.draw(&circle, DrawParam::default());
                     }
-                } else if cell.flagged {
-                    //Draw a flag
-                    let flag = Mesh::new_rounded_rectangle(
-                        ctx,
-                        DrawMode::fill(),
-                        Rect::new(
-                            rect.center().x - 0.025 * rect.w,
-                            rect.top() + 0.24 * rect.h,
-                            0.05 * consts::QUAD_SIZE.0,
-                            0.52 * rect.h,
-                        ),
-                        0.1 * consts::QUAD_SIZE.0,
-                        consts::FLAG_COLOR,
-                    )?;
-
-                    canvas.draw(&flag, DrawParam::default());
-
-                    let flag = Mesh::new_polygon(
-                        ctx,
-                        DrawMode::fill(),
-                        &[
-                            Point2 {
-                                x: rect.center().x,
-                                y: rect.top() + 0.24 * rect.h,
-                            },
-                            Point2 {
-                                x: rect.center().x + 0.3 * rect.w,
-                                y: rect.top() + 0.37 * rect.h,
-                            },
-                            Point2 {
-                                x: rect.center().x,
-                                y: rect.top() + 0.5 * rect.h,
-                            },
-                        ],
-                        consts::FLAG_COLOR,
-                    )?;
-                    canvas.draw(&flag, DrawParam::default());
-                } else if cell.question_marked {
-                    draw_text(
-                        canvas,
-                        "?",
-                        (
-                            rect.left() + 0.4 * consts::QUAD_SIZE.1,
-                            rect.top() + 0.4 * consts::QUAD_SIZE.1,
-                        ),
-                        0.8 * consts::QUAD_SIZE.1,
-                        TextLayout::center(),
-                        consts::QUESTION_MARK_COLOR,
-                    )?;
+                    Face::Flag => {
+                        //Draw a flag
+                        let flag = Mesh::new_rounded_rectangle(
+                            ctx,
+                            DrawMode::fill(),
+                            Rect::new(
+                                rect.center().x - 0.025 * rect.w,
+                                rect.top() + 0.24 * rect.h,
+                                0.05 * self.quad_size.0,
+                                0.52 * rect.h,
+                            ),
+                            0.1 * self.quad_size.0,
+                            colors().flag,
+                        )?;
+
+                        canvas.draw(&flag, DrawParam::default());
+
+                        let flag = Mesh::new_polygon(
+                            ctx,
+                            DrawMode::fill(),
+                            &[
+                                Point2 {
+                                    x: rect.center().x,
+                                    y: rect.top() + 0.24 * rect.h,
+                                },
+                                Point2 {
+                                    x: rect.center().x + 0.3 * rect.w,
+                                    y: rect.top() + 0.37 * rect.h,
+                                },
+                                Point2 {
+                                    x: rect.center().x,
+                                    y: rect.top() + 0.5 * rect.h,
+                                },
+                            ],
+                            colors().flag,
+                        )?;
+                        canvas.draw(&flag, DrawParam::default());
+                    }
+                    Face::QuestionMark => {
+                        draw_text(
+                            canvas,
+                            "?",
+                            (
+                                rect.left() + 0.4 * self.quad_size.1,
+                                rect.top() + 0.4 * self.quad_size.1,
+                            ),
+                            0.8 * self.quad_size.1,
+                            TextLayout::center(),
+                            colors().question_mark,
+                        )?;
+                    }
+                    Face::Blank => {}
                 }
             }
         }
+
+        if let Some(hint) = self.hint.as_ref().filter(|_| !self.is_paused()) {
+            let rect = self.cell_rect(hint.cell.0, hint.cell.1);
+            let outline = Mesh::new_rounded_rectangle(
+                ctx,
+                DrawMode::stroke(0.06 * self.quad_size.0),
+                rect,
+                0.2 * self.quad_size.0,
+                colors().question_mark,
+            )?;
+            canvas.draw(&outline, DrawParam::default());
+            if let Some(probability) = hint.probability {
+                draw_text(
+                    canvas,
+                    format!("{:.0}%", 100.0 * probability).as_str(),
+                    (rect.center().x, rect.center().y),
+                    0.35 * self.quad_size.1,
+                    TextLayout::center(),
+                    colors().question_mark,
+                )?;
+            }
+        }
+
+        if let Some(banner) = &self.banner {
+            let width = grid_x as f32 * self.quad_size.0;
+            let center_y = self.quad_size.1 * (1.0 + 0.5 * grid_y as f32);
+            let text_size = (0.8 * self.quad_size.1).min(1.5 * width / banner.len() as f32);
+            let band = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                Rect::new(0.0, center_y - text_size, width, 2.0 * text_size),
+                colors().button_clicked,
+            )?;
+            canvas.draw(&band, DrawParam::default());
+            draw_text(canvas, banner, (0.5 * width, center_y), text_size, TextLayout::center(), colors().flag)?;
+        }
         Ok(())
     }
 
     pub fn mouse_motion_event(&mut self, x_pos: f32, y_pos: f32) {
-        if self.game_state != GameState::Playing {
+        self.hover(self.cell_position(x_pos, y_pos));
+    }
+
+    pub fn mouse_button_down_event(&mut self, x_pos: f32, y_pos: f32) {
+        self.press(self.cell_position(x_pos, y_pos));
+    }
+
+    pub fn mouse_button_up_event(&mut self, button: MouseButton, x_pos: f32, y_pos: f32) -> GameState {
+        self.click(button, self.cell_position(x_pos, y_pos))
+    }
+
+    // the pointer over a cell, frontends work out which one themselves
+    pub fn hover(&mut self, hovered: Option<(usize, usize)>) {
+        if self.rules.state() != GameState::Playing || self.is_paused() {
             return;
         }
-        if let Some((cell_x, cell_y)) = Self::cell_position(x_pos, y_pos) {
-            self.grid.set_hovered(cell_x, cell_y, true);
-            if let Some((last_cell_x, last_cell_y)) = self.last_hovered_cell {
-                if last_cell_x != cell_x || last_cell_y != cell_y {
-                    self.grid.set_hovered(last_cell_x, last_cell_y, false);
-                    self.grid.set_clicked(last_cell_x, last_cell_y, false);
-                    self.last_hovered_cell = Some((cell_x, cell_y));
-                }
-            } else {
-                self.last_hovered_cell = Some((cell_x, cell_y));
-            }
-        } else if let Some((last_cell_x, last_cell_y)) = self.last_hovered_cell {
-            self.grid.set_hovered(last_cell_x, last_cell_y, false);
-            self.grid.set_clicked(last_cell_x, last_cell_y, false);
-            self.last_hovered_cell = None;
+        if hovered != self.last_hovered_cell {
+            self.pressed_cell = None;
+            self.last_hovered_cell = hovered;
         }
     }
 
-    pub fn mouse_button_down_event(&mut self, x_pos: f32, y_pos: f32) {
-        if self.game_state != GameState::Playing {
+    pub fn press(&mut self, pressed: Option<(usize, usize)>) {
+        if self.rules.state() != GameState::Playing || self.is_paused() {
             return;
         }
-        if let Some((cell_x, cell_y)) = Self::cell_position(x_pos, y_pos) {
-            self.grid.set_clicked(cell_x, cell_y, true);
+        if let Some(cell) = pressed {
+            self.pressed_cell = Some(cell);
         }
     }
 
-    pub fn mouse_button_up_event(&mut self, x_pos: f32, y_pos: f32) -> GameState {
-        if self.game_state != GameState::Playing {
-            return self.game_state;
+    pub fn click(&mut self, button: MouseButton, clicked: Option<(usize, usize)>) -> GameState {
+        if self.rules.state() != GameState::Playing {
+            return self.rules.state();
         }
-        if let Some((cell_x, cell_y)) = Self::cell_position(x_pos, y_pos) {
-            self.grid.set_clicked(cell_x, cell_y, false);
-            let cell = self.grid.get(cell_x, cell_y);
-
-            if cell.cleared {
+        // a click only resumes, the board was hidden
+        if self.is_paused() {
+            self.time.resume();
+            return GameState::Playing;
+        }
+        self.pressed_cell = None;
+        if let Some((cell_x, cell_y)) = clicked {
+            if self.rules.cell(cell_x, cell_y).cleared {
                 return GameState::Playing;
             }
-            if self.grid.set_cleared(cell_x, cell_y).is_none() {
-                return self.lose();
-            }
-            if self.grid.all_cleared() {
-                return self.win();
-            }
+            return match button {
+                MouseButton::Left => self.apply(Move::Clear(cell_x, cell_y)),
+                MouseButton::Right => self.apply(Move::Flag(cell_x, cell_y)),
+                _ => GameState::Playing,
+            };
         }
         GameState::Playing
     }
 
+    // every change made to the board goes through here so that it is recorded
+    fn apply(&mut self, played: Move) -> GameState {
+        if self.rules.state() != GameState::Playing {
+            return self.rules.state();
+        }
+        self.hint = None;
+        self.steps.push(Step {
+            time: self
+                .first_click
+                .map(|first_click| self.time.elapsed() - first_click)
+                .unwrap_or(0.0),
+            played,
+        });
+        let game_state = self.rules.play(played);
+        if self.first_click.is_none() && self.rules.is_started() {
+            self.first_click = Some(self.time.elapsed());
+        }
+        if game_state != GameState::Playing {
+            self.final_time = self.time.elapsed();
+        }
+        game_state
+    }
+
     pub fn mouse_enter_or_leave(&mut self, entered: bool) {
-        if self.game_state != GameState::Playing {
+        if self.rules.state() != GameState::Playing {
             return;
         }
         if !entered {
-            if let Some((last_cell_x, last_cell_y)) = self.last_hovered_cell {
-                self.grid.set_hovered(last_cell_x, last_cell_y, false);
-                self.grid.set_clicked(last_cell_x, last_cell_y, false);
-                self.last_hovered_cell = None;
-            }
+            self.last_hovered_cell = None;
+            self.pressed_cell = None;
+        }
+    }
+
+    fn move_to(&mut self, cell: (usize, usize)) {
+        self.last_hovered_cell = Some(cell);
+        self.pressed_cell = None;
+    }
+
+    // closest to the cursor of the safe cells, or else of the least likely mines
+    fn find_hint(&self) -> Option<Hint> {
+        let (x, y) = self.last_hovered_cell.unwrap_or((0, 0));
+        let distance = |cell: &(usize, usize)| cell.0.abs_diff(x) + cell.1.abs_diff(y);
+
+        if !self.rules.is_started() {
+            let (width, height) = self.rules.shape();
+            return match self.rules.safe_zone() {
+                SafeZone::None => None,
+                _ => Some(Hint {
+                    cell: (width / 2, height / 2),
+                    probability: None,
+                }),
+            };
         }
+
+        let analysis = self.rules.solver().analyse();
+        let safe = analysis
+            .safe
+            .into_iter()
+            .filter(|&(x, y)| !self.rules.cell(x, y).cleared)
+            .min_by_key(distance);
+        if let Some(cell) = safe {
+            return Some(Hint {
+                cell,
+                probability: None,
+            });
+        }
+
+        let interior = analysis
+            .interior
+            .iter()
+            .min_by_key(|cell| distance(cell))
+            .zip(analysis.interior_probability);
+        analysis
+            .probabilities
+            .into_iter()
+            .chain(interior.map(|(&cell, probability)| (cell, probability)))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(distance(&a.0).cmp(&distance(&b.0))))
+            .map(|(cell, probability)| Hint {
+                cell,
+                probability: Some(probability),
+            })
+    }
+
+    fn show_hint(&mut self) {
+        if self.hint.is_some() {
+            return;
+        }
+        self.hint = self.find_hint();
+        if self.hint.is_some() {
+            self.hints_used += 1;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.rules.state() == GameState::Playing && self.time.is_paused() && !self.held
     }
 
-    fn move_from_to(&mut self, from: (usize, usize), to: (usize, usize)) {
-        self.grid.set_hovered(to.0, to.1, true);
-        self.last_hovered_cell = Some(to);
-        self.grid.set_hovered(from.0, from.1, false);
-        self.grid.set_clicked(from.0, from.1, false);
+    pub fn pause(&mut self) {
+        if self.rules.state() == GameState::Playing {
+            self.time.pause();
+        }
     }
 
     pub fn handle(&mut self, action: Action) -> GameState {
-        if self.game_state != GameState::Playing {
-            return self.game_state;
+        if self.rules.state() != GameState::Playing {
+            return self.rules.state();
+        }
+        if let Action::Pause = action {
+            if self.is_paused() {
+                self.time.resume();
+            } else {
+                self.time.pause();
+            }
+            return GameState::Playing;
+        }
+        if self.is_paused() {
+            return GameState::Playing;
+        }
+        if let Action::Hint = action {
+            self.show_hint();
+            return GameState::Playing;
         }
         if let Some((x, y)) = self.last_hovered_cell {
             match action {
-                Action::Clear => {
-                    if self.grid.set_cleared(x, y).is_none() {
-                        return self.lose();
-                    }
-                    if self.grid.all_cleared() {
-                        return self.win();
-                    }
-                }
-                Action::Flag => self.grid.toggle_flagged(x, y),
-                Action::QuestionMark => self.grid.toggle_question_marked(x, y),
-                Action::ClearAdjacent => {
-                    if self.grid.clear_adjacent(x, y).is_none() {
-                        return self.lose();
-                    }
-                    if self.grid.all_cleared() {
-                        return self.win();
-                    }
-                }
+                Action::Clear => return self.apply(Move::Clear(x, y)),
+                Action::Flag => return self.apply(Move::Flag(x, y)),
+                Action::QuestionMark => return self.apply(Move::QuestionMark(x, y)),
+                Action::ClearAdjacent => return self.apply(Move::ClearAdjacent(x, y)),
                 _ => {}
             }
         }
         if let Action::Move(direction) = action {
             let (x, y) = self.last_hovered_cell.unwrap_or((0, 0));
+            let (width, height) = self.rules.shape();
             match direction {
                 Direction::Left => {
                     if x > 0 {
-                        self.move_from_to((x, y), (x - 1, y));
+                        self.move_to((x - 1, y));
                     }
                 }
                 Direction::Right => {
-                    if x < self.grid.get_shape().0 - 1 {
-                        self.move_from_to((x, y), (x + 1, y));
+                    if x < width - 1 {
+                        self.move_to((x + 1, y));
                     }
                 }
                 Direction::Up => {
                     if y > 0 {
-                        self.move_from_to((x, y), (x, y - 1));
+                        self.move_to((x, y - 1));
                     }
                 }
                 Direction::Down => {
-                    if y < self.grid.get_shape().1 - 1 {
-                        self.move_from_to((x, y), (x, y + 1))
+                    if y < height - 1 {
+                        self.move_to((x, y + 1))
                     }
                 }
             }
@@ -367,30 +658,70 @@ impl Game {
         GameState::Playing
     }
 
-    fn win(&mut self) -> GameState {
-        self.game_state = GameState::Won;
-        self.final_time = Instant::now().duration_since(self.time).as_secs_f32();
-        GameState::Won
+    pub fn get_hovered_cell(&self) -> Option<(usize, usize)> {
+        self.last_hovered_cell
     }
 
-    fn lose(&mut self) -> GameState {
-        for x in 0..self.grid.get_shape().0 {
-            for y in 0..self.grid.get_shape().1 {
-                let cell = self.grid.get(x, y);
-                if cell.cleared {
-                    continue;
-                }
-                if cell.get_value() == -1 {
-                    self.grid.set_cleared(x, y);
-                }
-            }
-        }
-        self.final_time = Instant::now().duration_since(self.time).as_secs_f32();
-        self.game_state = GameState::Lost;
-        GameState::Lost
+    // the hinted cell and its mine probability, hidden while paused like the rest of the board
+    pub fn get_hint(&self) -> Option<((usize, usize), Option<f64>)> {
+        self.hint
+            .as_ref()
+            .filter(|_| !self.is_paused())
+            .map(|hint| (hint.cell, hint.probability))
+    }
+
+    pub fn get_remaining_mines(&self) -> isize {
+        self.rules.remaining_mines()
+    }
+
+    pub fn get_banner(&self) -> Option<&str> {
+        self.banner.as_deref()
+    }
+
+    pub fn get_game_state(&self) -> GameState {
+        self.rules.state()
+    }
+
+    pub fn get_shape(&self) -> (usize, usize) {
+        self.rules.shape()
+    }
+
+    // nothing worth keeping before the first cell is cleared
+    pub fn is_started(&self) -> bool {
+        self.rules.is_started()
     }
 
     pub fn get_final_time(&self) -> f32 {
         self.final_time
     }
+
+    // from the first click to the end, the time spent looking at the board before it is left out
+    pub fn get_played_time(&self) -> f32 {
+        self.first_click
+            .map_or(0.0, |first_click| (self.final_time - first_click).max(0.0))
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.rules.seed()
+    }
+
+    pub fn get_safe_zone(&self) -> SafeZone {
+        self.rules.safe_zone()
+    }
+
+    pub fn get_hints_used(&self) -> usize {
+        self.hints_used
+    }
+
+    pub fn get_clicks(&self) -> Clicks {
+        self.rules.clicks()
+    }
+
+    pub fn get_3bv(&self) -> Option<usize> {
+        self.rules.bbbv()
+    }
+
+    pub fn get_solved_3bv(&self) -> usize {
+        self.rules.solved_bbbv()
+    }
 }
@@ -0,0 +1,117 @@
+use std::time::Instant;
+
+use ggez::graphics::Canvas;
+use ggez::{Context, GameResult};
+pub use minezweeper_engine::Replay;
+
+use super::{Game, GameState};
+
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 8.0;
+
+pub struct Player {
+    replay: Replay,
+    game: Game,
+    next_step: usize,
+    position: f32,
+    speed: f32,
+    paused: bool,
+    last_update: Instant,
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        let mut player = Player {
+            game: Game::from_replay(&replay),
+            replay,
+            next_step: 0,
+            position: 0.0,
+            speed: 1.0,
+            paused: false,
+            last_update: Instant::now(),
+        };
+        player.update_caption();
+        player
+    }
+
+    pub fn get_shape(&self) -> (usize, usize) {
+        self.replay.shape
+    }
+
+    pub fn set_quad_size(&mut self, quad_size: (f32, f32)) {
+        self.game.set_quad_size(quad_size);
+    }
+
+    // the clock of the game stops along with the player
+    fn update_caption(&mut self) {
+        self.game.hold_clock(self.paused);
+        self.game.set_caption(if self.paused {
+            format!("paused x{}", self.speed)
+        } else {
+            format!("x{}", self.speed)
+        });
+    }
+
+    fn play_next(&mut self) {
+        if let Some(step) = self.replay.steps.get(self.next_step) {
+            self.game.apply(step.played);
+            self.next_step += 1;
+        }
+    }
+
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        if !self.paused && self.game.get_game_state() == GameState::Playing {
+            self.position += self.speed * now.duration_since(self.last_update).as_secs_f32();
+            while self
+                .replay
+                .steps
+                .get(self.next_step)
+                .is_some_and(|step| step.time <= self.position)
+            {
+                self.play_next();
+            }
+            self.game.set_elapsed(self.position);
+        }
+        self.last_update = now;
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        self.game.draw(ctx, canvas)
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.update_caption();
+    }
+
+    // pauses and jumps to the next recorded move
+    pub fn step(&mut self) {
+        self.paused = true;
+        if let Some(step) = self.replay.steps.get(self.next_step) {
+            self.position = self.position.max(step.time);
+        }
+        self.play_next();
+        self.game.set_elapsed(self.position);
+        self.update_caption();
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (2.0 * self.speed).min(MAX_SPEED);
+        self.update_caption();
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (0.5 * self.speed).max(MIN_SPEED);
+        self.update_caption();
+    }
+
+    pub fn restart(&mut self) {
+        let quad_size = self.game.quad_size;
+        self.game = Game::from_replay(&self.replay);
+        self.game.set_quad_size(quad_size);
+        self.next_step = 0;
+        self.position = 0.0;
+        self.update_caption();
+    }
+}
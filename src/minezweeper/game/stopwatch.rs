@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+// elapsed time that stands still while paused
+pub struct Stopwatch {
+    started: Option<Instant>,
+    accumulated: Duration,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Stopwatch {
+            started: Some(Instant::now()),
+            accumulated: Duration::ZERO,
+        }
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        let running = self.started.map(|started| started.elapsed()).unwrap_or_default();
+        (self.accumulated + running).as_secs_f32()
+    }
+
+    pub fn set_elapsed(&mut self, elapsed: f32) {
+        self.accumulated = Duration::from_secs_f32(elapsed.max(0.0));
+        if self.started.is_some() {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.started.is_none()
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.accumulated += started.elapsed();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use crate::consts;
-use crate::minezweeper::draw_text;
-use ggez::graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextLayout};
+use crate::minezweeper::{config::colors, draw_text};
+use ggez::graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout};
 use ggez::mint::Point2;
 use ggez::{Context, GameResult};
 
 pub struct Button {
     text: String,
+    badge: Option<String>,
+    text_scale: f32,
     rect: Rect,
     pub hovered: bool,
     pub clicked: bool,
@@ -15,6 +16,8 @@ impl Button {
     pub fn new(text: String, rect: Rect) -> Button {
         Button {
             text,
+            badge: None,
+            text_scale: 1.0,
             rect,
             hovered: false,
             clicked: false,
@@ -28,11 +31,11 @@ impl Button {
         param: impl Into<DrawParam>,
     ) -> GameResult {
         let color = if self.clicked {
-            consts::BUTTON_CLICKED_COLOR
+            colors().button_clicked
         } else if self.hovered {
-            consts::BUTTON_HOVERED_COLOR
+            colors().button_hovered
         } else {
-            consts::BUTTON_COLOR
+            colors().button
         };
         let rectangle = Mesh::new_rounded_rectangle(
             ctx,
@@ -50,15 +53,46 @@ impl Button {
                 self.rect.left() + 0.5 * self.rect.w,
                 self.rect.top() + 0.5 * self.rect.h,
             ),
-            self.rect.h,
+            self.text_scale * self.rect.h,
             TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
+            colors().text,
         )?;
 
+        if let Some(badge) = &self.badge {
+            draw_text(
+                canvas,
+                badge.as_str(),
+                (
+                    self.rect.right() - 0.1 * self.rect.h,
+                    self.rect.top() + 0.1 * self.rect.h,
+                ),
+                0.25 * self.rect.h,
+                TextLayout {
+                    h_align: TextAlign::End,
+                    v_align: TextAlign::Begin,
+                },
+                colors().question_mark,
+            )?;
+        }
+
         Ok(())
     }
 
+    // for the rows of option buttons, whose labels are long for their height
+    pub fn small(mut self) -> Button {
+        self.text_scale = 0.6;
+        self
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
+    pub fn set_badge(&mut self, badge: Option<String>) {
+        self.badge = badge;
+    }
+
     pub fn point_inside(&self, x: f32, y: f32) -> bool {
-        return self.rect.contains(Point2 { x, y });
+        self.rect.contains(Point2 { x, y })
     }
 }
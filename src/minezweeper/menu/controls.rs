@@ -0,0 +1,187 @@
+use ggez::graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout};
+use ggez::input::keyboard::KeyCode;
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+use crate::consts;
+use crate::minezweeper::settings::{Action, Controls};
+use crate::minezweeper::{config::{self, colors}, draw_text};
+
+const ROW_HEIGHT: f32 = 0.8 * consts::QUAD_SIZE.1;
+const TOP_MARGIN: f32 = 0.5 * consts::QUAD_SIZE.1;
+
+pub struct ControlsScreen {
+    hovered: Option<usize>,
+    selected: usize,
+    // the next key pressed is bound to the selected action
+    waiting: bool,
+    message: Option<String>,
+    // bindings are saved for this profile
+    profile: String,
+}
+
+impl ControlsScreen {
+    pub fn standard(profile: &str) -> Self {
+        ControlsScreen {
+            hovered: None,
+            selected: 0,
+            waiting: false,
+            message: None,
+            profile: profile.to_string(),
+        }
+    }
+
+    fn row_rect(row: usize) -> Rect {
+        Rect::new(
+            0.05 * consts::CONTROLS_SCREEN_SIZE.0,
+            TOP_MARGIN + row as f32 * ROW_HEIGHT,
+            0.9 * consts::CONTROLS_SCREEN_SIZE.0,
+            0.9 * ROW_HEIGHT,
+        )
+    }
+
+    fn row_at(x: f32, y: f32) -> Option<usize> {
+        (0..Action::BINDABLE.len()).find(|&row| Self::row_rect(row).contains(Point2 { x, y }))
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, controls: &Controls) -> GameResult {
+        let left = TextLayout {
+            h_align: TextAlign::Begin,
+            v_align: TextAlign::Middle,
+        };
+        for (row, action) in Action::BINDABLE.iter().enumerate() {
+            let rect = Self::row_rect(row);
+            let color = match (row == self.selected, self.waiting) {
+                (true, true) => Some(colors().button_clicked),
+                (true, false) => Some(colors().button_hovered),
+                _ if self.hovered == Some(row) => Some(colors().button),
+                _ => None,
+            };
+            if let Some(color) = color {
+                let rectangle = Mesh::new_rounded_rectangle(ctx, DrawMode::fill(), rect, 0.2 * rect.h, color)?;
+                canvas.draw(&rectangle, DrawParam::default());
+            }
+
+            draw_text(
+                canvas,
+                action.label(),
+                (rect.left() + 0.2 * rect.h, rect.center().y),
+                0.6 * rect.h,
+                left,
+                colors().text,
+            )?;
+            let keys = if row == self.selected && self.waiting {
+                "press a key".to_string()
+            } else {
+                controls
+                    .keys(*action)
+                    .iter()
+                    .map(|keycode| format!("{:?}", keycode))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            draw_text(
+                canvas,
+                keys.as_str(),
+                (rect.left() + 0.45 * rect.w, rect.center().y),
+                0.6 * rect.h,
+                left,
+                colors().text,
+            )?;
+        }
+
+        let bottom = TOP_MARGIN + Action::BINDABLE.len() as f32 * ROW_HEIGHT;
+        draw_text(
+            canvas,
+            self.message
+                .as_deref()
+                .unwrap_or("Return: add key  Delete: clear  R: reset"),
+            (0.5 * consts::CONTROLS_SCREEN_SIZE.0, bottom + 0.5 * ROW_HEIGHT),
+            0.4 * ROW_HEIGHT,
+            TextLayout::center(),
+            if self.message.is_some() {
+                colors().flag
+            } else {
+                colors().text
+            },
+        )?;
+        Ok(())
+    }
+
+    fn save(&mut self, controls: &Controls) {
+        if let Err(err) = config::save_controls(&self.profile, controls) {
+            self.message = Some(format!("Error saving controls: {}", err));
+        }
+    }
+
+    // escape cancels the key being captured rather than quitting
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    // returns false when the key was not used, so that Back can leave the screen
+    pub fn key_down_event(&mut self, keycode: KeyCode, controls: &mut Controls) -> bool {
+        if self.waiting {
+            if keycode == KeyCode::Back || keycode == KeyCode::Escape {
+                self.waiting = false;
+                return true;
+            }
+            match controls.bind(Action::BINDABLE[self.selected], keycode) {
+                Ok(()) => {
+                    self.waiting = false;
+                    self.message = None;
+                    self.save(controls);
+                }
+                Err(err) => self.message = Some(err.to_string()),
+            }
+            return true;
+        }
+        match keycode {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(Action::BINDABLE.len() - 1),
+            KeyCode::Return => {
+                self.waiting = true;
+                self.message = None;
+            }
+            KeyCode::Delete => {
+                controls.unbind(Action::BINDABLE[self.selected]);
+                self.save(controls);
+            }
+            KeyCode::R => {
+                *controls = Controls::default();
+                self.message = None;
+                self.save(controls);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn mouse_button_up_event(&mut self, x: f32, y: f32) {
+        if let Some(row) = Self::row_at(x, y) {
+            self.selected = row;
+            self.waiting = true;
+            self.message = None;
+        }
+    }
+
+    pub fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        self.hovered = Self::row_at(x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_cancels_the_capture_without_binding_it() {
+        let mut screen = ControlsScreen::standard(config::DEFAULT_PROFILE);
+        let mut controls = Controls::default();
+        assert!(screen.key_down_event(KeyCode::Return, &mut controls));
+        assert!(screen.is_waiting());
+        assert!(screen.key_down_event(KeyCode::Escape, &mut controls));
+        assert!(!screen.is_waiting());
+        assert_eq!(controls.keys(Action::BINDABLE[0]), Controls::default().keys(Action::BINDABLE[0]));
+    }
+}
@@ -0,0 +1,149 @@
+use ggez::graphics::{self, Canvas, TextLayout};
+use ggez::{Context, GameResult};
+use super::buttons::Button;
+use super::text_input::TextInput;
+use crate::consts;
+use crate::minezweeper::{config::colors, draw_text, Level};
+use minezweeper_engine::MAX_SIDE;
+
+pub enum CustomSelected {
+    Play, None
+}
+
+pub struct Custom {
+    inputs: [TextInput; 3],
+    play_button: Button,
+    seed: Option<u64>,
+    error: Option<String>,
+}
+
+impl Custom {
+
+    pub fn standard(seed: Option<u64>) -> Self {
+
+        let (button_width, button_height) = consts::BUTTON_SIZE;
+        let horizontal_margin = 0.5 * (consts::CUSTOM_SCREEN_SIZE.0 - button_width);
+        let vertical_margin = 0.2 * button_height;
+        // a side is at most MAX_SIDE, and all but one cell can be mines
+        let side_digits = MAX_SIDE.to_string().len();
+        let mines_digits = (MAX_SIDE * MAX_SIDE - 1).to_string().len();
+        let input = |label: &str, row: f32, digits: usize| TextInput::new(
+            label.to_string(),
+            graphics::Rect::new(
+                horizontal_margin, vertical_margin + row * (consts::QUAD_SIZE.1 + vertical_margin),
+                button_width, consts::QUAD_SIZE.1,
+            ),
+            digits,
+        );
+        let mut custom = Custom {
+            inputs: [
+                input("width", 0.0, side_digits),
+                input("height", 1.0, side_digits),
+                input("mines", 2.0, mines_digits),
+            ],
+            play_button: Button::new(
+                "Play".to_string(),
+                graphics::Rect::new(
+                    horizontal_margin, vertical_margin + 3.0 * (consts::QUAD_SIZE.1 + vertical_margin),
+                    button_width, button_height,
+                )
+            ),
+            seed,
+            error: None,
+        };
+        custom.inputs[0].focused = true;
+        custom
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn level(&self) -> Level {
+        let [width, height, mines] = &self.inputs;
+        Level::Custom {
+            width: width.value().unwrap_or(0) as usize,
+            height: height.value().unwrap_or(0) as usize,
+            mines: mines.value().unwrap_or(0) as usize,
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        for input in self.inputs.iter() {
+            input.draw(ctx, canvas, graphics::DrawParam::default())?;
+        }
+        self.play_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        if let Some(error) = &self.error {
+            draw_text(
+                canvas,
+                error,
+                (
+                    0.5 * consts::CUSTOM_SCREEN_SIZE.0,
+                    consts::CUSTOM_SCREEN_SIZE.1 - 0.5 * consts::QUAD_SIZE.1,
+                ),
+                0.3 * consts::QUAD_SIZE.1,
+                TextLayout::center(),
+                colors().flag,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn text_input_event(&mut self, character: char) {
+        for input in self.inputs.iter_mut() {
+            input.push(character);
+        }
+    }
+
+    pub fn erase_character(&mut self) -> bool {
+        self.inputs.iter_mut().any(|input| input.pop())
+    }
+
+    pub fn focus_next(&mut self) {
+        let focused = self.inputs.iter().position(|input| input.focused);
+        for input in self.inputs.iter_mut() {
+            input.focused = false;
+        }
+        self.inputs[focused.map(|i| (i + 1) % 3).unwrap_or(0)].focused = true;
+    }
+
+    pub fn mouse_button_down_event(
+        &mut self,
+        x: f32,
+        y: f32,
+    ) {
+        self.play_button.clicked = self.play_button.point_inside(x, y);
+    }
+
+    pub fn mouse_button_up_event(
+        &mut self,
+        x: f32,
+        y: f32,
+    ) -> CustomSelected {
+        if self.play_button.point_inside(x, y) {
+            return CustomSelected::Play;
+        }
+        for input in self.inputs.iter_mut() {
+            input.focused = input.point_inside(x, y);
+        }
+        CustomSelected::None
+    }
+
+    pub fn mouse_motion_event(
+        &mut self,
+        x: f32,
+        y: f32,
+    ) {
+        self.play_button.hovered = self.play_button.point_inside(x, y);
+        if !self.play_button.hovered {
+            self.play_button.clicked = false
+        }
+        for input in self.inputs.iter_mut() {
+            input.hovered = input.point_inside(x, y);
+        }
+    }
+}
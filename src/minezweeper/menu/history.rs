@@ -0,0 +1,540 @@
+use chrono::{Duration, Local};
+use ggez::graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout};
+use ggez::input::keyboard::KeyCode;
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+use crate::consts;
+use crate::minezweeper::{
+    config::colors, draw_text, game::{replay::Replay, GameState}, menu::LEVELS, settings::Score,
+    store::ScoreStore, Level,
+};
+
+const PAGE_SIZE: usize = 10;
+const ROW_HEIGHT: f32 = 0.5 * consts::BUTTON_SIZE.1;
+// the filters and the column names come before the rows
+const HEADER_ROWS: usize = 2;
+const TEXT_SIZE: f32 = 0.6 * ROW_HEIGHT;
+const OUTCOMES: [GameState; 3] = [GameState::Won, GameState::Lost, GameState::Abandoned];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Order {
+    Newest,
+    Oldest,
+    Fastest,
+    Slowest,
+}
+
+impl Order {
+    fn label(&self) -> &str {
+        match self {
+            Order::Newest => "newest",
+            Order::Oldest => "oldest",
+            Order::Fastest => "fastest",
+            Order::Slowest => "slowest",
+        }
+    }
+
+    fn next(&self) -> Order {
+        match self {
+            Order::Newest => Order::Oldest,
+            Order::Oldest => Order::Fastest,
+            Order::Fastest => Order::Slowest,
+            Order::Slowest => Order::Newest,
+        }
+    }
+
+    fn compare(&self, a: &Score, b: &Score) -> std::cmp::Ordering {
+        match self {
+            Order::Newest => b.date_time.cmp(&a.date_time),
+            Order::Oldest => a.date_time.cmp(&b.date_time),
+            Order::Fastest => a.time.total_cmp(&b.time),
+            Order::Slowest => b.time.total_cmp(&a.time),
+        }
+    }
+}
+
+// how far back games are listed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Period {
+    All,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    fn label(&self) -> &str {
+        match self {
+            Period::All => "all time",
+            Period::Day => "last day",
+            Period::Week => "last week",
+            Period::Month => "last month",
+            Period::Year => "last year",
+        }
+    }
+
+    fn next(&self) -> Period {
+        match self {
+            Period::All => Period::Day,
+            Period::Day => Period::Week,
+            Period::Week => Period::Month,
+            Period::Month => Period::Year,
+            Period::Year => Period::All,
+        }
+    }
+
+    fn contains(&self, score: &Score) -> bool {
+        let days = match self {
+            Period::All => return true,
+            Period::Day => 1,
+            Period::Week => 7,
+            Period::Month => 30,
+            Period::Year => 365,
+        };
+        score.date_time >= Local::now() - Duration::days(days)
+    }
+}
+
+// cycles from no filter through every option and back
+fn cycle<T: Copy + PartialEq>(current: Option<T>, options: &[T]) -> Option<T> {
+    match current.and_then(|current| options.iter().position(|option| *option == current)) {
+        None => options.first().copied(),
+        Some(index) => options.get(index + 1).copied(),
+    }
+}
+
+pub struct History {
+    scores: Vec<Score>,
+    // the standard levels and every custom size that was played
+    levels: Vec<Level>,
+    level: Option<Level>,
+    outcome: Option<GameState>,
+    period: Period,
+    order: Order,
+    // indices in scores of the games that pass the filters, in order
+    shown: Vec<usize>,
+    selected: usize,
+    hovered: Option<usize>,
+    details: bool,
+    error: Option<String>,
+}
+
+impl History {
+    // only the games of the profile being played
+    pub fn standard(store: &dyn ScoreStore, profile: &str) -> Self {
+        let (scores, error) = match store.all() {
+            Ok(scores) => (scores.into_iter().filter(|score| score.profile == profile).collect(), None),
+            Err(error) => (Vec::new(), Some(error.to_string())),
+        };
+        let mut levels = LEVELS.to_vec();
+        for score in scores.iter() {
+            if !levels.contains(&score.level) {
+                levels.push(score.level);
+            }
+        }
+        let mut history = History {
+            scores,
+            levels,
+            level: None,
+            outcome: None,
+            period: Period::All,
+            order: Order::Newest,
+            shown: Vec::new(),
+            selected: 0,
+            hovered: None,
+            details: false,
+            error,
+        };
+        history.refresh();
+        history
+    }
+
+    fn refresh(&mut self) {
+        let mut shown: Vec<usize> = (0..self.scores.len())
+            .filter(|&i| {
+                let score = &self.scores[i];
+                self.level.is_none_or(|level| score.level == level)
+                    && self.outcome.is_none_or(|outcome| score.game_state == outcome)
+                    && self.period.contains(score)
+            })
+            .collect();
+        shown.sort_by(|&a, &b| self.order.compare(&self.scores[a], &self.scores[b]));
+        self.shown = shown;
+        self.selected = 0;
+        self.details = false;
+    }
+
+    fn page(&self) -> usize {
+        self.selected / PAGE_SIZE
+    }
+
+    fn pages(&self) -> usize {
+        self.shown.len().div_ceil(PAGE_SIZE).max(1)
+    }
+
+    fn page_scores(&self) -> impl Iterator<Item = &Score> {
+        self.shown
+            .iter()
+            .skip(self.page() * PAGE_SIZE)
+            .take(PAGE_SIZE)
+            .map(|&i| &self.scores[i])
+    }
+
+    fn selected_score(&self) -> Option<&Score> {
+        self.shown.get(self.selected).map(|&i| &self.scores[i])
+    }
+
+    // only while the details of a game with a replay are shown
+    pub fn selected_replay(&self) -> Option<Replay> {
+        self.details
+            .then(|| self.selected_score().and_then(|score| score.replay.clone()))
+            .flatten()
+    }
+
+    fn go_to_page(&mut self, page: usize) {
+        self.selected = page.min(self.pages() - 1) * PAGE_SIZE;
+    }
+
+    // returns false when the details were not open, so that Back can leave the screen
+    pub fn close_details(&mut self) -> bool {
+        std::mem::replace(&mut self.details, false)
+    }
+
+    pub fn key_down_event(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::L => {
+                self.level = cycle(self.level, &self.levels);
+                self.refresh();
+            }
+            KeyCode::O => {
+                self.outcome = cycle(self.outcome, &OUTCOMES);
+                self.refresh();
+            }
+            KeyCode::D => {
+                self.period = self.period.next();
+                self.refresh();
+            }
+            KeyCode::S => {
+                self.order = self.order.next();
+                self.refresh();
+            }
+            KeyCode::Up if !self.details => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if !self.details => {
+                self.selected = (self.selected + 1).min(self.shown.len().saturating_sub(1))
+            }
+            KeyCode::Left | KeyCode::PageUp if !self.details => self.go_to_page(self.page().saturating_sub(1)),
+            KeyCode::Right | KeyCode::PageDown if !self.details => self.go_to_page(self.page() + 1),
+            KeyCode::Return => self.details = !self.shown.is_empty(),
+            _ => {}
+        }
+    }
+
+    fn row_rect(row: usize) -> Rect {
+        Rect::new(
+            0.02 * consts::HISTORY_SCREEN_SIZE.0,
+            (HEADER_ROWS + row) as f32 * ROW_HEIGHT,
+            0.96 * consts::HISTORY_SCREEN_SIZE.0,
+            ROW_HEIGHT,
+        )
+    }
+
+    fn footer_y() -> f32 {
+        (HEADER_ROWS + PAGE_SIZE) as f32 * ROW_HEIGHT + 0.5 * ROW_HEIGHT
+    }
+
+    // the previous and next page arrows on both ends of the footer
+    fn arrow_rect(next: bool) -> Rect {
+        let x = if next { 0.85 } else { 0.05 } * consts::HISTORY_SCREEN_SIZE.0;
+        Rect::new(x, Self::footer_y() - 0.5 * ROW_HEIGHT, 0.1 * consts::HISTORY_SCREEN_SIZE.0, ROW_HEIGHT)
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.page_scores().count()).find(|&row| Self::row_rect(row).contains(Point2 { x, y }))
+    }
+
+    pub fn mouse_button_up_event(&mut self, x: f32, y: f32) {
+        if self.details {
+            self.details = false;
+        } else if let Some(row) = self.row_at(x, y) {
+            self.selected = self.page() * PAGE_SIZE + row;
+            self.details = true;
+        } else if Self::arrow_rect(false).contains(Point2 { x, y }) {
+            self.go_to_page(self.page().saturating_sub(1));
+        } else if Self::arrow_rect(true).contains(Point2 { x, y }) {
+            self.go_to_page(self.page() + 1);
+        }
+    }
+
+    pub fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        self.hovered = self.row_at(x, y);
+    }
+
+    fn draw_columns(canvas: &mut Canvas, columns: &[&str], y_pos: f32) -> GameResult {
+        const X: [f32; 7] = [0.04, 0.28, 0.44, 0.58, 0.68, 0.8, 0.9];
+        for (column, x) in columns.iter().zip(X) {
+            draw_text(
+                canvas,
+                column,
+                (x * consts::HISTORY_SCREEN_SIZE.0, y_pos),
+                TEXT_SIZE,
+                TextLayout {
+                    h_align: TextAlign::Begin,
+                    v_align: TextAlign::Middle,
+                },
+                colors().text,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn draw_list(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        Self::draw_columns(canvas, &["date", "level", "outcome", "time", "3BV/s", "hints", "replay"], 1.5 * ROW_HEIGHT)?;
+        for (row, score) in self.page_scores().enumerate() {
+            let rect = Self::row_rect(row);
+            let color = if self.page() * PAGE_SIZE + row == self.selected {
+                Some(colors().button_hovered)
+            } else if self.hovered == Some(row) {
+                Some(colors().button)
+            } else {
+                None
+            };
+            if let Some(color) = color {
+                let rectangle = Mesh::new_rounded_rectangle(ctx, DrawMode::fill(), rect, 0.2 * rect.h, color)?;
+                canvas.draw(&rectangle, DrawParam::default());
+            }
+            Self::draw_columns(
+                canvas,
+                &[
+                    score.date_time.format("%Y-%m-%d %H:%M").to_string().as_str(),
+                    score.level.label().as_str(),
+                    score.game_state.to_string().as_str(),
+                    format!("{:.1}", score.time).as_str(),
+                    score
+                        .bbbv_per_second
+                        .map(|bbbv_per_second| format!("{:.2}", bbbv_per_second))
+                        .unwrap_or_default()
+                        .as_str(),
+                    score.hints.to_string().as_str(),
+                    if score.replay.is_some() { "yes" } else { "" },
+                ],
+                rect.center().y,
+            )?;
+        }
+
+        let footer = format!("{} games   page {}/{}", self.shown.len(), self.page() + 1, self.pages());
+        for (text, x) in [
+            ("<", Self::arrow_rect(false).center().x),
+            (footer.as_str(), 0.5 * consts::HISTORY_SCREEN_SIZE.0),
+            (">", Self::arrow_rect(true).center().x),
+        ] {
+            draw_text(canvas, text, (x, Self::footer_y()), TEXT_SIZE, TextLayout::center(), colors().text)?;
+        }
+        Ok(())
+    }
+
+    fn draw_details(&self, canvas: &mut Canvas, score: &Score) -> GameResult {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        let lines = [
+            ("level", score.level.label()),
+            ("outcome", score.game_state.to_string()),
+            ("played", score.date_time.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("time", format!("{:.2}s", score.time)),
+            ("first click", score.safe_zone.label().to_string()),
+            ("seed", optional(score.seed.map(|seed| seed.to_string()))),
+            ("hints", score.hints.to_string()),
+            ("3BV", optional(score.bbbv.map(|bbbv| bbbv.to_string()))),
+            ("3BV/s", optional(score.bbbv_per_second.map(|value| format!("{:.2}", value)))),
+            (
+                "clicks",
+                optional(score.clicks.map(|clicks| {
+                    format!("{} ({} left, {} right, {} chord)", clicks.total(), clicks.left, clicks.right, clicks.chord)
+                })),
+            ),
+            ("efficiency", optional(score.efficiency.map(|efficiency| format!("{:.0}%", efficiency)))),
+        ];
+        let left = TextLayout {
+            h_align: TextAlign::Begin,
+            v_align: TextAlign::Middle,
+        };
+        for (row, (name, value)) in lines.iter().enumerate() {
+            let y_pos = Self::row_rect(row).center().y - ROW_HEIGHT;
+            draw_text(canvas, name, (0.1 * consts::HISTORY_SCREEN_SIZE.0, y_pos), TEXT_SIZE, left, colors().text)?;
+            draw_text(canvas, value, (0.35 * consts::HISTORY_SCREEN_SIZE.0, y_pos), TEXT_SIZE, left, colors().text)?;
+        }
+        draw_text(
+            canvas,
+            if score.replay.is_some() {
+                "R: watch the replay  Back: list"
+            } else {
+                "no replay recorded  Back: list"
+            },
+            (0.5 * consts::HISTORY_SCREEN_SIZE.0, Self::footer_y()),
+            TEXT_SIZE,
+            TextLayout::center(),
+            colors().text,
+        )
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if let Some(error) = &self.error {
+            return draw_text(
+                canvas,
+                error,
+                (0.5 * consts::HISTORY_SCREEN_SIZE.0, 0.5 * consts::HISTORY_SCREEN_SIZE.1),
+                TEXT_SIZE,
+                TextLayout::center(),
+                colors().text,
+            );
+        }
+        match self.selected_score().filter(|_| self.details) {
+            Some(score) => self.draw_details(canvas, score),
+            None => {
+                let filters = format!(
+                    "L: {}   O: {}   D: {}   S: {}",
+                    self.level.map(|level| level.label()).unwrap_or("all levels".to_string()),
+                    self.outcome.map(|outcome| outcome.to_string()).unwrap_or("all outcomes".to_string()),
+                    self.period.label(),
+                    self.order.label(),
+                );
+                draw_text(
+                    canvas,
+                    filters.as_str(),
+                    (0.5 * consts::HISTORY_SCREEN_SIZE.0, 0.5 * ROW_HEIGHT),
+                    TEXT_SIZE,
+                    TextLayout::center(),
+                    colors().text,
+                )?;
+                self.draw_list(ctx, canvas)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::config::DEFAULT_PROFILE;
+    use crate::minezweeper::store::MemoryStore;
+
+    fn score(level: Level, game_state: GameState, time: f32, days_ago: i64) -> Score {
+        Score {
+            date_time: Local::now() - Duration::days(days_ago) - Duration::minutes(time as i64),
+            ..Score::for_test(level, game_state, time)
+        }
+    }
+
+    fn history(scores: &[Score]) -> History {
+        let mut store = MemoryStore::default();
+        store.save_all(scores).unwrap();
+        History::standard(&store, DEFAULT_PROFILE)
+    }
+
+    fn times(history: &History) -> Vec<f32> {
+        history.shown.iter().map(|&i| history.scores[i].time).collect()
+    }
+
+    #[test]
+    fn filters() {
+        let custom = Level::Custom { width: 20, height: 20, mines: 50 };
+        let mut history = history(&[
+            score(Level::Easy, GameState::Won, 1.0, 0),
+            score(Level::Easy, GameState::Lost, 2.0, 3),
+            score(Level::Hard, GameState::Won, 3.0, 20),
+            score(custom, GameState::Abandoned, 4.0, 200),
+        ]);
+        assert_eq!(history.levels.last(), Some(&custom));
+        assert_eq!(times(&history), vec![1.0, 2.0, 3.0, 4.0]);
+
+        history.key_down_event(KeyCode::L);
+        assert_eq!(history.level, Some(Level::Easy));
+        assert_eq!(times(&history), vec![1.0, 2.0]);
+
+        history.key_down_event(KeyCode::O);
+        assert_eq!(times(&history), vec![1.0]);
+
+        history.level = None;
+        history.outcome = None;
+        history.key_down_event(KeyCode::D);
+        assert_eq!(times(&history), vec![1.0]);
+        history.key_down_event(KeyCode::D);
+        assert_eq!(times(&history), vec![1.0, 2.0]);
+        history.key_down_event(KeyCode::D);
+        assert_eq!(times(&history), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn only_the_games_of_the_profile() {
+        let mut other = score(Level::Hard, GameState::Won, 2.0, 0);
+        other.profile = "alice".to_string();
+        let history = history(&[score(Level::Easy, GameState::Won, 1.0, 0), other]);
+        assert_eq!(times(&history), vec![1.0]);
+        assert_eq!(history.levels, LEVELS.to_vec());
+    }
+
+    #[test]
+    fn filters_cycle_back_to_all() {
+        let mut history = history(&[score(Level::Easy, GameState::Won, 1.0, 0)]);
+        for _ in 0..=OUTCOMES.len() {
+            history.key_down_event(KeyCode::O);
+        }
+        assert_eq!(history.outcome, None);
+        for _ in 0..=LEVELS.len() {
+            history.key_down_event(KeyCode::L);
+        }
+        assert_eq!(history.level, None);
+    }
+
+    #[test]
+    fn orders() {
+        let mut history = history(&[
+            score(Level::Easy, GameState::Won, 5.0, 1),
+            score(Level::Easy, GameState::Won, 9.0, 0),
+            score(Level::Easy, GameState::Won, 2.0, 2),
+        ]);
+        assert_eq!(times(&history), vec![9.0, 5.0, 2.0]);
+        history.key_down_event(KeyCode::S);
+        assert_eq!(times(&history), vec![2.0, 5.0, 9.0]);
+        history.key_down_event(KeyCode::S);
+        assert_eq!(times(&history), vec![2.0, 5.0, 9.0]);
+        history.key_down_event(KeyCode::S);
+        assert_eq!(times(&history), vec![9.0, 5.0, 2.0]);
+    }
+
+    #[test]
+    fn pagination() {
+        let scores: Vec<Score> = (0..2 * PAGE_SIZE + 3)
+            .map(|day| score(Level::Medium, GameState::Lost, 1.0, day as i64))
+            .collect();
+        let mut history = history(&scores);
+        assert_eq!(history.pages(), 3);
+        history.key_down_event(KeyCode::Right);
+        assert_eq!((history.page(), history.selected), (1, PAGE_SIZE));
+        history.key_down_event(KeyCode::Right);
+        history.key_down_event(KeyCode::Right);
+        assert_eq!(history.page(), 2);
+        assert_eq!(history.page_scores().count(), 3);
+        history.key_down_event(KeyCode::Up);
+        assert_eq!(history.page(), 1);
+        history.key_down_event(KeyCode::Left);
+        history.key_down_event(KeyCode::Left);
+        assert_eq!((history.page(), history.selected), (0, 0));
+    }
+
+    #[test]
+    fn details_and_replay() {
+        let mut history = history(&[score(Level::Easy, GameState::Won, 1.0, 0)]);
+        assert!(!history.close_details());
+        history.key_down_event(KeyCode::Return);
+        assert!(history.details);
+        assert!(history.selected_replay().is_none());
+        assert!(history.close_details());
+        assert!(!history.details);
+
+        let mut empty = super::History::standard(&MemoryStore::default(), DEFAULT_PROFILE);
+        empty.key_down_event(KeyCode::Return);
+        assert!(!empty.details);
+        assert_eq!(empty.pages(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+use ggez::graphics::{Canvas, TextAlign, TextLayout};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::consts;
+use crate::minezweeper::{
+    config::colors, draw_text, game::{GameState, SafeZone}, menu::LEVELS, settings::Score, store::ScoreStore, Level,
+};
+
+pub const LEADERBOARD_SIZE: usize = 10;
+const ROW_HEIGHT: f32 = 0.5 * consts::BUTTON_SIZE.1;
+const TEXT_SIZE: f32 = 0.6 * ROW_HEIGHT;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Board {
+    All,
+    // wins without a single right click
+    NoFlag,
+}
+
+impl Board {
+    pub const ALL: [Board; 2] = [Board::All, Board::NoFlag];
+
+    fn label(&self) -> &str {
+        match self {
+            Board::All => "all wins",
+            Board::NoFlag => "no flag wins",
+        }
+    }
+
+    fn next(&self) -> Board {
+        match self {
+            Board::All => Board::NoFlag,
+            Board::NoFlag => Board::All,
+        }
+    }
+
+    // games that used hints never make it, old games without clicks can not be told apart as no flag
+    fn admits(&self, score: &Score) -> bool {
+        score.game_state == GameState::Won
+            && score.hints == 0
+            && match self {
+                Board::All => true,
+                Board::NoFlag => score.clicks.is_some_and(|clicks| clicks.right == 0),
+            }
+    }
+}
+
+// the fastest wins of a level, compared only under the same first click policy
+pub fn top(scores: &[Score], level: Level, safe_zone: SafeZone, board: Board) -> Vec<&Score> {
+    let mut top: Vec<&Score> = scores
+        .iter()
+        .filter(|score| score.level == level && score.safe_zone == safe_zone && board.admits(score))
+        .collect();
+    top.sort_by(|a, b| a.time.total_cmp(&b.time).then(a.date_time.cmp(&b.date_time)));
+    top.truncate(LEADERBOARD_SIZE);
+    top
+}
+
+// the place, counted from 1, a new score takes among the earlier ones, if it makes the board
+pub fn rank(scores: &[Score], score: &Score, board: Board) -> Option<usize> {
+    if !board.admits(score) {
+        return None;
+    }
+    let faster = top(scores, score.level, score.safe_zone, board)
+        .iter()
+        .filter(|other| other.time <= score.time)
+        .count();
+    (faster < LEADERBOARD_SIZE).then_some(faster + 1)
+}
+
+// a win that makes one of the profile's own leaderboards is announced on the board
+pub fn personal_best(store: &dyn ScoreStore, score: &Score) -> Option<String> {
+    let scores: Vec<Score> = store
+        .all()
+        .ok()?
+        .into_iter()
+        .filter(|other| other.profile == score.profile)
+        .collect();
+    Board::ALL.iter().find_map(|board| {
+        rank(&scores, score, *board).map(|rank| match board {
+            Board::All => format!("new personal best! #{}", rank),
+            Board::NoFlag => format!("new no flag best! #{}", rank),
+        })
+    })
+}
+
+pub struct Leaderboard {
+    scores: Vec<Score>,
+    levels: Vec<Level>,
+    level: usize,
+    board: Board,
+    safe_zone: SafeZone,
+    error: Option<String>,
+}
+
+impl Leaderboard {
+    // the profile's own wins, as its personal bests are counted
+    pub fn standard(store: &dyn ScoreStore, safe_zone: SafeZone, profile: &str) -> Self {
+        let (scores, error) = match store.all() {
+            Ok(scores) => (scores.into_iter().filter(|score| score.profile == profile).collect(), None),
+            Err(error) => (Vec::new(), Some(error.to_string())),
+        };
+        let mut levels = LEVELS.to_vec();
+        for score in scores.iter() {
+            if !levels.contains(&score.level) {
+                levels.push(score.level);
+            }
+        }
+        Leaderboard {
+            scores,
+            levels,
+            level: 0,
+            board: Board::All,
+            safe_zone,
+            error,
+        }
+    }
+
+    pub fn key_down_event(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Left => self.level = (self.level + self.levels.len() - 1) % self.levels.len(),
+            KeyCode::Right => self.level = (self.level + 1) % self.levels.len(),
+            KeyCode::N => self.board = self.board.next(),
+            KeyCode::F => self.safe_zone = self.safe_zone.next(&SafeZone::ALL),
+            _ => {}
+        }
+    }
+
+    fn draw_columns(canvas: &mut Canvas, columns: &[&str], y_pos: f32) -> GameResult {
+        const X: [f32; 5] = [0.05, 0.15, 0.32, 0.5, 0.78];
+        for (column, x) in columns.iter().zip(X) {
+            draw_text(
+                canvas,
+                column,
+                (x * consts::LEADERBOARD_SCREEN_SIZE.0, y_pos),
+                TEXT_SIZE,
+                TextLayout {
+                    h_align: TextAlign::Begin,
+                    v_align: TextAlign::Middle,
+                },
+                colors().text,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if let Some(error) = &self.error {
+            return draw_text(
+                canvas,
+                error,
+                (0.5 * consts::LEADERBOARD_SCREEN_SIZE.0, 0.5 * consts::LEADERBOARD_SCREEN_SIZE.1),
+                TEXT_SIZE,
+                TextLayout::center(),
+                colors().text,
+            );
+        }
+        let level = self.levels[self.level];
+        let title = format!("< {} >   N: {}   F: {}", level.label(), self.board.label(), self.safe_zone.label());
+        draw_text(
+            canvas,
+            title.as_str(),
+            (0.5 * consts::LEADERBOARD_SCREEN_SIZE.0, 0.5 * ROW_HEIGHT),
+            TEXT_SIZE,
+            TextLayout::center(),
+            colors().text,
+        )?;
+        Self::draw_columns(canvas, &["#", "time", "3BV/s", "date", "player"], 1.5 * ROW_HEIGHT)?;
+        for (place, score) in top(&self.scores, level, self.safe_zone, self.board).iter().enumerate() {
+            Self::draw_columns(
+                canvas,
+                &[
+                    (place + 1).to_string().as_str(),
+                    format!("{:.2}", score.time).as_str(),
+                    score
+                        .bbbv_per_second
+                        .map(|bbbv_per_second| format!("{:.2}", bbbv_per_second))
+                        .unwrap_or_default()
+                        .as_str(),
+                    score.date_time.format("%Y-%m-%d").to_string().as_str(),
+                    score.player.as_deref().unwrap_or(""),
+                ],
+                (2.5 + place as f32) * ROW_HEIGHT,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::config::DEFAULT_PROFILE;
+    use crate::minezweeper::game::Clicks;
+    use crate::minezweeper::store::MemoryStore;
+    use chrono::{Duration, Local};
+
+    fn win(level: Level, time: f32, right_clicks: Option<usize>) -> Score {
+        Score {
+            date_time: Local::now() - Duration::seconds(time as i64),
+            clicks: right_clicks.map(|right| Clicks { left: 10, right, chord: 0 }),
+            ..Score::for_test(level, GameState::Won, time)
+        }
+    }
+
+    #[test]
+    fn top_is_fastest_first_and_capped() {
+        let mut scores: Vec<Score> = (0..LEADERBOARD_SIZE + 5)
+            .rev()
+            .map(|time| win(Level::Easy, time as f32 + 1.0, Some(1)))
+            .collect();
+        scores.push(win(Level::Medium, 0.5, Some(1)));
+        let mut lost = win(Level::Easy, 0.1, Some(0));
+        lost.game_state = GameState::Lost;
+        scores.push(lost);
+        let mut assisted = win(Level::Easy, 0.1, Some(0));
+        assisted.hints = 1;
+        scores.push(assisted);
+
+        let times: Vec<f32> = top(&scores, Level::Easy, SafeZone::Opening, Board::All)
+            .iter()
+            .map(|score| score.time)
+            .collect();
+        assert_eq!(times, (1..=LEADERBOARD_SIZE).map(|time| time as f32).collect::<Vec<f32>>());
+        assert!(top(&scores, Level::Easy, SafeZone::Cell, Board::All).is_empty());
+    }
+
+    #[test]
+    fn no_flag_board() {
+        let custom = Level::Custom { width: 12, height: 12, mines: 20 };
+        let scores = vec![
+            win(custom, 10.0, Some(0)),
+            win(custom, 5.0, Some(3)),
+            win(custom, 7.0, None),
+        ];
+        let no_flag: Vec<f32> = top(&scores, custom, SafeZone::Opening, Board::NoFlag)
+            .iter()
+            .map(|score| score.time)
+            .collect();
+        assert_eq!(no_flag, vec![10.0]);
+        assert_eq!(top(&scores, custom, SafeZone::Opening, Board::All).len(), 3);
+    }
+
+    #[test]
+    fn rank_of_a_new_win() {
+        let scores: Vec<Score> = (1..=LEADERBOARD_SIZE)
+            .map(|time| win(Level::Hard, 10.0 * time as f32, Some(2)))
+            .collect();
+        assert_eq!(rank(&scores, &win(Level::Hard, 5.0, Some(2)), Board::All), Some(1));
+        assert_eq!(rank(&scores, &win(Level::Hard, 25.0, Some(2)), Board::All), Some(3));
+        assert_eq!(rank(&scores, &win(Level::Hard, 1000.0, Some(2)), Board::All), None);
+        assert_eq!(rank(&scores, &win(Level::Hard, 1000.0, Some(0)), Board::NoFlag), Some(1));
+        assert_eq!(rank(&scores, &win(Level::Hard, 5.0, Some(2)), Board::NoFlag), None);
+        assert_eq!(rank(&[], &win(Level::Easy, 99.0, Some(2)), Board::All), Some(1));
+    }
+
+    #[test]
+    fn only_the_wins_of_the_profile() {
+        let mut store = MemoryStore::default();
+        let mut other = win(Level::Easy, 1.0, Some(0));
+        other.profile = "alice".to_string();
+        store.save_all(&[win(Level::Easy, 2.0, Some(0)), other]).unwrap();
+        let leaderboard = Leaderboard::standard(&store, SafeZone::Opening, DEFAULT_PROFILE);
+        assert_eq!(leaderboard.scores.len(), 1);
+        assert_eq!(leaderboard.scores[0].time, 2.0);
+    }
+}
@@ -1,24 +1,44 @@
 mod buttons;
+mod text_input;
+pub mod custom;
 pub mod settings;
 pub mod scores;
 pub mod controls;
-use crate::{consts, minezweeper::Level};
+pub mod history;
+pub mod leaderboard;
+use crate::{consts, minezweeper::{game::{Generation, SafeZone}, settings::SavedGame, Level}};
 use buttons::Button;
+use text_input::TextInput;
 use ggez::{graphics::{self, Canvas}, GameResult, Context};
 
 const LEVELS: [Level; 3] = [
     Level::Easy, Level::Medium, Level::Hard
 ];
 
+// rows of smaller buttons below the levels
+const OPTION_ROWS: f32 = 4.0;
+
 pub enum Selected {
     Level(Level),
+    Continue,
+    Custom,
     Settings,
+    SafeZone,
+    Generation,
+    Profile,
     None,
 }
 
 pub struct Menu {
     buttons: [Button; 3],
+    custom_button: Button,
+    // only offered while a game is saved
+    continue_button: Option<Button>,
     setting_button: Button,
+    seed_input: TextInput,
+    safe_zone_button: Button,
+    generation_button: Button,
+    profile_button: Button,
 }
 
 pub trait ButtonSize {
@@ -52,43 +72,155 @@ impl ButtonSize for Level {
                     button_height,
                 )
             }
+            Self::Custom { .. } => {
+                graphics::Rect::new(
+                    horizontal_margin,
+                    4.0 * vertical_margin + 3.0 * button_height,
+                    button_width,
+                    button_height,
+                )
+            }
         }
     }
 }
 
 impl Menu {
-    pub fn standard() -> Self {
+    pub fn standard(safe_zone: SafeZone, generation: Generation, profile: &str) -> Self {
         let (button_width, button_height) = consts::BUTTON_SIZE;
         let horizontal_margin = 0.5 * (consts::SCREEN_SIZE.0 - button_width);
-        let vertical_margin = 0.25 * (consts::SCREEN_SIZE.1 - 3.5 * button_height);
+        let vertical_margin = 0.25 * (consts::SCREEN_SIZE.1 - 3.5 * button_height - OPTION_ROWS * 0.6 * button_height);
+        let option_row = |row: f32| 3.0 * vertical_margin + 3.0 * button_height + button_height * (0.33 + 0.6 * row);
+        let half_width = 0.5 * (button_width - 0.2 * consts::QUAD_SIZE.0);
+        let continue_button = SavedGame::exists(profile).then(|| Button::new(
+            "Continue".to_string(),
+            graphics::Rect::new(
+                horizontal_margin + button_width - half_width,
+                option_row(0.0),
+                half_width,
+                consts::QUAD_SIZE.1,
+            )
+        ));
 
-        Menu {
+        let mut menu = Menu {
             buttons: LEVELS.map(|level|
                 Button::new(
                     level.level_info().name,
                     level.button_rect(button_width, button_height, horizontal_margin, vertical_margin)
                 )
             ),
+            custom_button: Button::new(
+                "Custom".to_string(),
+                graphics::Rect::new(
+                    horizontal_margin,
+                    option_row(0.0),
+                    if continue_button.is_some() { half_width } else { button_width },
+                    consts::QUAD_SIZE.1,
+                )
+            ),
+            continue_button,
             setting_button: Button::new(
                 "*".to_string(),
                 graphics::Rect::new(
-                    consts::SCREEN_SIZE.0 * 0.5 - consts::QUAD_SIZE.0*0.5,
-                    3.0 * vertical_margin + 3.0 * button_height + button_height*0.33,
+                    horizontal_margin + button_width - consts::QUAD_SIZE.0,
+                    option_row(1.0),
                     consts::QUAD_SIZE.0,
                     consts::QUAD_SIZE.1,
                 )
-            )
-        }
+            ),
+            seed_input: TextInput::new(
+                "seed".to_string(),
+                graphics::Rect::new(
+                    horizontal_margin,
+                    option_row(1.0),
+                    button_width - 1.2 * consts::QUAD_SIZE.0,
+                    consts::QUAD_SIZE.1,
+                ),
+                10,
+            ),
+            safe_zone_button: Button::new(
+                safe_zone.label().to_string(),
+                graphics::Rect::new(
+                    horizontal_margin,
+                    option_row(2.0),
+                    half_width,
+                    consts::QUAD_SIZE.1,
+                )
+            ).small(),
+            generation_button: Button::new(
+                generation.label().to_string(),
+                graphics::Rect::new(
+                    horizontal_margin + button_width - half_width,
+                    option_row(2.0),
+                    half_width,
+                    consts::QUAD_SIZE.1,
+                )
+            ).small(),
+            profile_button: Button::new(
+                String::new(),
+                graphics::Rect::new(
+                    horizontal_margin,
+                    option_row(3.0),
+                    button_width,
+                    consts::QUAD_SIZE.1,
+                )
+            ).small(),
+        };
+        menu.set_generation(generation);
+        menu.set_profile(profile);
+        menu
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         for button in self.buttons.iter() {
             button.draw(ctx, canvas, graphics::DrawParam::default())?;
         }
+        self.custom_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        if let Some(continue_button) = &self.continue_button {
+            continue_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        }
         self.setting_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.seed_input.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.safe_zone_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.generation_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.profile_button.draw(ctx, canvas, graphics::DrawParam::default())?;
         Ok(())
     }
 
+    pub fn seed(&self) -> Option<u64> {
+        self.seed_input.value()
+    }
+
+    pub fn set_safe_zone(&mut self, safe_zone: SafeZone) {
+        self.safe_zone_button.set_text(safe_zone.label().to_string());
+    }
+
+    // level buttons are badged while boards are generated without guessing
+    pub fn set_generation(&mut self, generation: Generation) {
+        self.generation_button.set_text(generation.label().to_string());
+        for button in self.buttons.iter_mut() {
+            button.set_badge(match generation {
+                Generation::Classic => None,
+                Generation::NoGuess => Some("NG".to_string()),
+            });
+        }
+    }
+
+    pub fn set_profile(&mut self, profile: &str) {
+        self.profile_button.set_text(format!("profile: {}", profile));
+    }
+
+    pub fn typing_seed(&self) -> bool {
+        self.seed_input.focused
+    }
+
+    pub fn text_input_event(&mut self, character: char) {
+        self.seed_input.push(character);
+    }
+
+    pub fn erase_character(&mut self) {
+        self.seed_input.pop();
+    }
+
     pub fn mouse_button_down_event(
         &mut self,
         x: f32,
@@ -97,22 +229,47 @@ impl Menu {
         for button in self.buttons.iter_mut() {
             button.clicked = button.point_inside(x, y);
         }
+        self.custom_button.clicked = self.custom_button.point_inside(x, y);
+        if let Some(continue_button) = &mut self.continue_button {
+            continue_button.clicked = continue_button.point_inside(x, y);
+        }
         self.setting_button.clicked = self.setting_button.point_inside(x, y);
+        self.safe_zone_button.clicked = self.safe_zone_button.point_inside(x, y);
+        self.generation_button.clicked = self.generation_button.point_inside(x, y);
+        self.profile_button.clicked = self.profile_button.point_inside(x, y);
     }
 
     pub fn mouse_button_up_event(
-        &self,
+        &mut self,
         x: f32,
         y: f32,
     ) -> Selected {
+        self.seed_input.focused = self.seed_input.point_inside(x, y);
         for (i, button) in self.buttons.iter().enumerate() {
             if button.point_inside(x, y) {
                 return Selected::Level(LEVELS[i]);
             }
         }
+        if self.custom_button.point_inside(x, y) {
+            return Selected::Custom;
+        }
+        if let Some(continue_button) = &self.continue_button {
+            if continue_button.point_inside(x, y) {
+                return Selected::Continue;
+            }
+        }
         if self.setting_button.point_inside(x, y) {
             return Selected::Settings;
         }
+        if self.safe_zone_button.point_inside(x, y) {
+            return Selected::SafeZone;
+        }
+        if self.generation_button.point_inside(x, y) {
+            return Selected::Generation;
+        }
+        if self.profile_button.point_inside(x, y) {
+            return Selected::Profile;
+        }
         Selected::None
     }
 
@@ -127,9 +284,32 @@ impl Menu {
                 button.clicked = false
             }
         }
+        self.custom_button.hovered = self.custom_button.point_inside(x, y);
+        if !self.custom_button.hovered {
+            self.custom_button.clicked = false
+        }
+        if let Some(continue_button) = &mut self.continue_button {
+            continue_button.hovered = continue_button.point_inside(x, y);
+            if !continue_button.hovered {
+                continue_button.clicked = false
+            }
+        }
         self.setting_button.hovered = self.setting_button.point_inside(x, y);
         if !self.setting_button.hovered {
             self.setting_button.clicked = false
         }
+        self.seed_input.hovered = self.seed_input.point_inside(x, y);
+        self.safe_zone_button.hovered = self.safe_zone_button.point_inside(x, y);
+        if !self.safe_zone_button.hovered {
+            self.safe_zone_button.clicked = false
+        }
+        self.generation_button.hovered = self.generation_button.point_inside(x, y);
+        if !self.generation_button.hovered {
+            self.generation_button.clicked = false
+        }
+        self.profile_button.hovered = self.profile_button.point_inside(x, y);
+        if !self.profile_button.hovered {
+            self.profile_button.clicked = false
+        }
     }
 }
@@ -1,191 +1,614 @@
 use std::collections::HashMap;
 
-use ggez::graphics::{Canvas, TextAlign, TextLayout};
+use ggez::graphics::{Canvas, DrawParam, Mesh, TextAlign, TextLayout};
+use ggez::input::keyboard::KeyCode;
+use ggez::mint::Point2;
 use ggez::{Context, GameResult};
 
 use crate::consts;
-use crate::minezweeper::{draw_text, game::GameState, menu::LEVELS, settings::Score, Level};
+use crate::minezweeper::{config::{self, colors}, draw_text, game::{replay::Replay, GameState, SafeZone}, menu::LEVELS, settings::Score, store::ScoreStore, Level};
 
-#[derive(Debug)]
-struct Statistic {
+#[derive(Debug, Default)]
+pub struct Statistic {
     pub played: usize,
     pub won: usize,
     pub lost: usize,
     pub abandoned: usize,
+    // wins where hints were used, left out of the times
+    pub assisted: usize,
     pub best_time: Option<f32>,
     pub average_time: Option<f32>,
 }
 
+// most played custom sizes shown below the standard levels
+const CUSTOM_ROWS: usize = 4;
+const BEST_GAMES: usize = 5;
+// the win rate is taken over the latest games, the average time over the latest wins
+const RECENT_GAMES: usize = 20;
+const ROLLING_WINS: usize = 10;
+// rows taken by the chart below the trends
+const CHART_ROWS: usize = 5;
+// profiles shown side by side, two columns each
+const COMPARED_PROFILES: usize = 4;
+
+#[derive(Debug, Default)]
+struct Trend {
+    current_streak: usize,
+    longest_streak: usize,
+    recent_win_rate: Option<f32>,
+    rolling_average: Option<f32>,
+    // times of the wins without hints, oldest first
+    win_times: Vec<f32>,
+}
+
+impl Trend {
+    // the scores have to be ordered by date, any game that is not won ends a streak
+    fn from_scores<'a>(scores: impl Iterator<Item = &'a Score>) -> Self {
+        let mut trend = Trend::default();
+        let mut outcomes = Vec::new();
+        for score in scores {
+            let won = score.game_state == GameState::Won;
+            trend.current_streak = if won { trend.current_streak + 1 } else { 0 };
+            trend.longest_streak = trend.longest_streak.max(trend.current_streak);
+            if won && score.hints == 0 {
+                trend.win_times.push(score.time);
+            }
+            outcomes.push(won);
+        }
+        let recent = &outcomes[outcomes.len().saturating_sub(RECENT_GAMES)..];
+        trend.recent_win_rate = (!recent.is_empty())
+            .then(|| 100.0 * recent.iter().filter(|&&won| won).count() as f32 / recent.len() as f32);
+        trend.rolling_average = Self::average(&trend.win_times);
+        trend
+    }
+
+    // the average of up to ROLLING_WINS times ending with the last one
+    fn average(times: &[f32]) -> Option<f32> {
+        let window = &times[times.len().saturating_sub(ROLLING_WINS)..];
+        (!window.is_empty()).then(|| window.iter().sum::<f32>() / window.len() as f32)
+    }
+
+    fn rolling_averages(&self) -> Vec<f32> {
+        (1..=self.win_times.len())
+            .filter_map(|end| Self::average(&self.win_times[..end]))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Comparison {
+    profile: String,
+    total: Statistic,
+    stats: HashMap<Level, Statistic>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Time,
+    Bbbv,
+    BbbvPerSecond,
+    Efficiency,
+}
+
+impl SortKey {
+    fn label(&self) -> &str {
+        match self {
+            SortKey::Time => "best by time",
+            SortKey::Bbbv => "best by 3BV",
+            SortKey::BbbvPerSecond => "best by 3BV/s",
+            SortKey::Efficiency => "best by efficiency",
+        }
+    }
+
+    pub fn next(&self) -> SortKey {
+        match self {
+            SortKey::Time => SortKey::Bbbv,
+            SortKey::Bbbv => SortKey::BbbvPerSecond,
+            SortKey::BbbvPerSecond => SortKey::Efficiency,
+            SortKey::Efficiency => SortKey::Time,
+        }
+    }
+
+    // best first, games missing the metric last
+    fn compare(&self, a: &Score, b: &Score) -> std::cmp::Ordering {
+        let descending = |a: Option<f32>, b: Option<f32>| b.unwrap_or(-1.0).total_cmp(&a.unwrap_or(-1.0));
+        match self {
+            SortKey::Time => a.time.total_cmp(&b.time),
+            SortKey::Bbbv => descending(a.bbbv.map(|bbbv| bbbv as f32), b.bbbv.map(|bbbv| bbbv as f32)),
+            SortKey::BbbvPerSecond => descending(a.bbbv_per_second, b.bbbv_per_second),
+            SortKey::Efficiency => descending(a.efficiency, b.efficiency),
+        }
+    }
+}
+
+impl Statistic {
+    pub fn from_scores<'a>(scores: impl Iterator<Item = &'a Score> + Clone) -> Self {
+        let won_count = scores
+            .clone()
+            .filter(|score| score.game_state == GameState::Won)
+            .count();
+        let won_scores = scores
+            .clone()
+            .filter(|score| score.game_state == GameState::Won && score.hints == 0);
+        let won_scores_count = won_scores.clone().count();
+        Statistic {
+            played: scores.clone().count(),
+            won: won_count,
+            lost: scores
+                .clone()
+                .filter(|score| score.game_state == GameState::Lost)
+                .count(),
+            abandoned: scores
+                .filter(|score| score.game_state == GameState::Abandoned)
+                .count(),
+            assisted: won_count - won_scores_count,
+            best_time: won_scores
+                .clone()
+                .min_by(|&a, &b| a.time.total_cmp(&b.time))
+                .map(|score| score.time),
+            average_time: won_scores
+                .fold(None, |acc, score| Some(acc.unwrap_or(0.0) + score.time))
+                .map(|f| f / (won_scores_count as f32)),
+        }
+    }
+
+    pub fn win_rate(&self) -> Option<f32> {
+        (self.played > 0).then(|| 100.0 * self.won as f32 / self.played as f32)
+    }
+}
+
 pub struct Scores {
     total_stats: Statistic,
+    levels: Vec<Level>,
     stats: HashMap<Level, Statistic>,
+    safe_zone: SafeZone,
+    best_games: Vec<Score>,
+    sort_key: SortKey,
+    latest_replay: Option<Replay>,
+    total_trend: Trend,
+    trends: HashMap<Level, Trend>,
+    show_trends: bool,
+    // the level whose times are drawn in the chart
+    chart_level: usize,
+    // None compares the profiles instead of showing a single one
+    profile: Option<String>,
+    compared: Vec<Comparison>,
     error: Option<String>,
 }
 
 impl Scores {
-    pub fn standard() -> Self {
-        match Score::all() {
+    // only games played under the same first click policy are compared
+    pub fn standard(store: &dyn ScoreStore, safe_zone: SafeZone, sort_key: SortKey, profile: Option<String>) -> Self {
+        match store.all() {
             Ok(scores) => {
+                let mut scores: Vec<Score> = scores
+                    .into_iter()
+                    .filter(|score| score.safe_zone == safe_zone)
+                    .filter(|score| profile.as_ref().is_none_or(|profile| score.profile == *profile))
+                    .collect();
+                scores.sort_by_key(|score| score.date_time);
+
+                // custom games are grouped by their exact size
+                let mut custom_levels: Vec<Level> = Vec::new();
+                for score in scores.iter() {
+                    if matches!(score.level, Level::Custom { .. }) && !custom_levels.contains(&score.level) {
+                        custom_levels.push(score.level);
+                    }
+                }
+                let played = |level: &Level| scores.iter().filter(|score| score.level == *level).count();
+                custom_levels.sort_by_key(|level| std::cmp::Reverse(played(level)));
+                custom_levels.truncate(CUSTOM_ROWS);
+
+                let levels: Vec<Level> = LEVELS.iter().copied().chain(custom_levels).collect();
+                let latest_replay = scores
+                    .iter()
+                    .filter(|score| score.replay.is_some())
+                    .max_by_key(|score| score.date_time)
+                    .and_then(|score| score.replay.clone());
+
+                let mut best_games: Vec<Score> = scores
+                    .iter()
+                    .filter(|score| score.game_state == GameState::Won && score.hints == 0)
+                    .cloned()
+                    .collect();
+                best_games.sort_by(|a, b| sort_key.compare(a, b));
+                best_games.truncate(BEST_GAMES);
                 let mut stats: HashMap<Level, Statistic> = HashMap::new();
-                for level in LEVELS.iter() {
-                    let scores = scores.iter().filter(|score| score.level == *level);
-                    let won_scores = scores
-                        .clone()
-                        .filter(|score| score.game_state == GameState::Won);
-                    let won_scores_count = won_scores.clone().count();
+                let mut trends: HashMap<Level, Trend> = HashMap::new();
+                for level in levels.iter() {
                     stats.insert(
                         *level,
-                        Statistic {
-                            played: scores.clone().count(),
-                            won: won_scores_count,
-                            lost: scores
-                                .clone()
-                                .filter(|score| score.game_state == GameState::Lost)
-                                .count(),
-                            abandoned: scores
-                                .filter(|score| score.game_state == GameState::Abandoned)
-                                .count(),
-                            best_time: won_scores
-                                .clone()
-                                .min_by(|&a, &b| a.time.total_cmp(&b.time))
-                                .map(|score| score.time),
-                            average_time: won_scores
-                                .fold(None, |acc, score| Some(acc.unwrap_or(0.0) + score.time))
-                                .map(|f| f / (won_scores_count as f32)),
-                        },
+                        Statistic::from_scores(scores.iter().filter(|score| score.level == *level)),
                     );
+                    trends.insert(*level, Trend::from_scores(scores.iter().filter(|score| score.level == *level)));
                 }
+                let compared = match profile {
+                    Some(_) => Vec::new(),
+                    None => Self::compared_profiles(&scores)
+                        .into_iter()
+                        .map(|profile| {
+                            let own = scores.iter().filter(|score| score.profile == profile);
+                            Comparison {
+                                total: Statistic::from_scores(own.clone()),
+                                stats: levels
+                                    .iter()
+                                    .map(|level| (*level, Statistic::from_scores(own.clone().filter(|score| score.level == *level))))
+                                    .collect(),
+                                profile,
+                            }
+                        })
+                        .collect(),
+                };
 
                 Scores {
-                    total_stats: Statistic {
-                        played: stats.values().fold(0, |acc, stat| acc + stat.played),
-                        won: stats.values().fold(0, |acc, stat| acc + stat.won),
-                        lost: stats.values().fold(0, |acc, stat| acc + stat.lost),
-                        abandoned: stats.values().fold(0, |acc, stat| acc + stat.abandoned),
-                        best_time: None,
-                        average_time: None,
-                    },
-                    stats: stats,
+                    total_stats: Statistic::from_scores(scores.iter()),
+                    levels,
+                    stats,
+                    safe_zone,
+                    best_games,
+                    sort_key,
+                    latest_replay,
+                    total_trend: Trend::from_scores(scores.iter()),
+                    trends,
+                    show_trends: false,
+                    chart_level: 0,
+                    profile,
+                    compared,
                     error: None,
                 }
             }
             Err(error) => Scores {
-                total_stats: Statistic {
-                    played: 0,
-                    won: 0,
-                    lost: 0,
-                    abandoned: 0,
-                    best_time: None,
-                    average_time: None,
-                },
+                total_stats: Statistic::default(),
+                levels: Vec::new(),
                 stats: HashMap::new(),
+                safe_zone,
+                best_games: Vec::new(),
+                sort_key,
+                latest_replay: None,
+                total_trend: Trend::default(),
+                trends: HashMap::new(),
+                show_trends: false,
+                chart_level: 0,
+                profile,
+                compared: Vec::new(),
                 error: Some(error.to_string()),
             },
         }
     }
 
-    fn draw_headers(
-        &self,
-        canvas: &mut Canvas,
-        played: &str,
-        won: &str,
-        lost: &str,
-        abandoned: &str,
-        win_percentage: &str,
-        best_time: &str,
-        average_time: &str,
-        y_pos: f32,
-    ) -> GameResult {
-        draw_text(
-            canvas,
-            played,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.22, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
-        )?;
-        draw_text(
-            canvas,
-            won,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.335, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
-        )?;
-        draw_text(
-            canvas,
-            lost,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.45, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
-        )?;
+    // the configured profiles first, then any other found in the scores, imported ones for example
+    fn compared_profiles(scores: &[Score]) -> Vec<String> {
+        let mut profiles: Vec<String> = config::profile_names()
+            .into_iter()
+            .filter(|profile| scores.iter().any(|score| score.profile == *profile))
+            .collect();
+        for score in scores.iter() {
+            if !profiles.contains(&score.profile) {
+                profiles.push(score.profile.clone());
+            }
+        }
+        profiles.truncate(COMPARED_PROFILES);
+        profiles
+    }
+
+    fn rows(&self) -> usize {
+        match self.best_games.len() {
+            _ if self.show_trends => self.levels.len() + CHART_ROWS,
+            _ if self.profile.is_none() => self.levels.len(),
+            0 => self.levels.len(),
+            games => self.levels.len() + 1 + games,
+        }
+    }
+
+    // trends only make sense for the games of a single profile
+    pub fn toggle_trends(&mut self) {
+        self.show_trends = !self.show_trends && self.profile.is_some();
+    }
+
+    pub fn key_down_event(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Up if self.show_trends => self.chart_level = self.chart_level.saturating_sub(1),
+            KeyCode::Down if self.show_trends => {
+                self.chart_level = (self.chart_level + 1).min(self.levels.len().saturating_sub(1))
+            }
+            _ => {}
+        }
+    }
+
+    fn row_y(row: usize) -> f32 {
+        consts::SCORES_SCREEN_SIZE.1 * (0.45 + (row as f32) * 0.2)
+    }
+
+    // the screen grows by a row for every custom size and best game shown
+    pub fn size(&self) -> (f32, f32) {
+        let extra_rows = self.rows().saturating_sub(LEVELS.len());
+        (
+            consts::SCORES_SCREEN_SIZE.0,
+            consts::SCORES_SCREEN_SIZE.1 * (1.0 + 0.2 * extra_rows as f32),
+        )
+    }
+
+    pub fn sort_key(&self) -> SortKey {
+        self.sort_key
+    }
+
+    pub fn safe_zone(&self) -> SafeZone {
+        self.safe_zone
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    // every profile on its own, then all of them side by side
+    pub fn next_profile(&self) -> Option<String> {
+        let profiles = config::profile_names();
+        match &self.profile {
+            None => profiles.first().cloned(),
+            Some(profile) => profiles.iter().skip_while(|name| *name != profile).nth(1).cloned(),
+        }
+    }
+
+    fn total_label(&self) -> &str {
+        self.profile.as_deref().unwrap_or("Total")
+    }
+
+    pub fn latest_replay(&self) -> Option<Replay> {
+        self.latest_replay.clone()
+    }
+
+    fn draw_label(&self, canvas: &mut Canvas, label: &str, y_pos: f32) -> GameResult {
         draw_text(
             canvas,
-            abandoned,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.565, y_pos),
+            label,
+            (consts::SCORES_SCREEN_SIZE.0 * 0.05, y_pos),
             0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
-        )?;
-        draw_text(
+            TextLayout {
+                h_align: TextAlign::Begin,
+                v_align: TextAlign::Middle,
+            },
+            colors().text,
+        )
+    }
+
+    fn draw_best_games(&self, canvas: &mut Canvas) -> GameResult {
+        if self.best_games.is_empty() {
+            return Ok(());
+        }
+        let header = self.levels.len();
+        self.draw_label(canvas, self.sort_key.label(), Self::row_y(header))?;
+        self.draw_row(canvas, &["", "", "T", "3BV", "3BV/s", "CL", "EF"], Self::row_y(header))?;
+        for (i, score) in self.best_games.iter().enumerate() {
+            let y_pos = Self::row_y(header + 1 + i);
+            self.draw_label(canvas, &score.level.label(), y_pos)?;
+            let metric = |value: Option<f32>, precision: usize| {
+                value
+                    .map(|value| format!("{:.*}", precision, value))
+                    .unwrap_or_default()
+            };
+            self.draw_row(
+                canvas,
+                &[
+                    "",
+                    "",
+                    format!("{:.1}", score.time).as_str(),
+                    metric(score.bbbv.map(|bbbv| bbbv as f32), 0).as_str(),
+                    metric(score.bbbv_per_second, 2).as_str(),
+                    metric(score.clicks.map(|clicks| clicks.total() as f32), 0).as_str(),
+                    score
+                        .efficiency
+                        .map(|efficiency| format!("{:.0}%", efficiency))
+                        .unwrap_or_default()
+                        .as_str(),
+                ],
+                y_pos,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn draw_row(&self, canvas: &mut Canvas, columns: &[&str], y_pos: f32) -> GameResult {
+        for (i, column) in columns.iter().enumerate() {
+            draw_text(
+                canvas,
+                column,
+                (consts::SCORES_SCREEN_SIZE.0 * (0.22 + 0.1 * i as f32), y_pos),
+                0.7 * consts::QUAD_SIZE.1,
+                TextLayout::center(),
+                colors().text,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn trend_columns(trend: &Trend) -> [String; 4] {
+        let optional = |value: Option<f32>, suffix: &str| {
+            value
+                .map(|value| format!("{:.1}{}", value, suffix))
+                .unwrap_or_default()
+        };
+        [
+            trend.current_streak.to_string(),
+            trend.longest_streak.to_string(),
+            optional(trend.recent_win_rate, "%"),
+            optional(trend.rolling_average, ""),
+        ]
+    }
+
+    fn draw_trends(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let recent = format!("W%{}", RECENT_GAMES);
+        let rolling = format!("AT{}", ROLLING_WINS);
+        self.draw_row(
             canvas,
-            win_percentage,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.68, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
+            &["CS", "LS", recent.as_str(), rolling.as_str()],
+            consts::SCORES_SCREEN_SIZE.1 * 0.1,
         )?;
+        self.draw_label(canvas, self.total_label(), consts::SCORES_SCREEN_SIZE.1 * 0.25)?;
+        let columns = Self::trend_columns(&self.total_trend);
+        self.draw_row(canvas, &columns.each_ref().map(String::as_str), consts::SCORES_SCREEN_SIZE.1 * 0.25)?;
+        for (i, level) in self.levels.iter().enumerate() {
+            let label = match i == self.chart_level {
+                true => format!("> {}", level.label()),
+                false => level.label(),
+            };
+            self.draw_label(canvas, &label, Self::row_y(i))?;
+            let columns = Self::trend_columns(&self.trends[level]);
+            self.draw_row(canvas, &columns.each_ref().map(String::as_str), Self::row_y(i))?;
+        }
+        self.draw_chart(ctx, canvas)
+    }
 
-        draw_text(
+    // a dash for levels never played
+    fn win_rate(stat: &Statistic) -> String {
+        stat.win_rate()
+            .map(|win_rate| format!("{:.1}%", win_rate))
+            .unwrap_or("-".to_string())
+    }
+
+    // the win rate and best time of every profile, level by level
+    fn draw_comparison(&self, canvas: &mut Canvas) -> GameResult {
+        for (i, comparison) in self.compared.iter().enumerate() {
+            draw_text(
+                canvas,
+                &comparison.profile,
+                (consts::SCORES_SCREEN_SIZE.0 * (0.27 + 0.2 * i as f32), consts::SCORES_SCREEN_SIZE.1 * 0.05),
+                0.7 * consts::QUAD_SIZE.1,
+                TextLayout::center(),
+                colors().text,
+            )?;
+        }
+        let header: Vec<&str> = self.compared.iter().flat_map(|_| ["W%", "BT"]).collect();
+        self.draw_row(canvas, &header, consts::SCORES_SCREEN_SIZE.1 * 0.15)?;
+        self.draw_label(canvas, self.total_label(), consts::SCORES_SCREEN_SIZE.1 * 0.25)?;
+        let totals: Vec<String> = self
+            .compared
+            .iter()
+            .flat_map(|comparison| [Self::win_rate(&comparison.total), String::new()])
+            .collect();
+        self.draw_row(
             canvas,
-            best_time,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.795, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
+            &totals.iter().map(String::as_str).collect::<Vec<&str>>(),
+            consts::SCORES_SCREEN_SIZE.1 * 0.25,
         )?;
+        for (i, level) in self.levels.iter().enumerate() {
+            self.draw_label(canvas, &level.label(), Self::row_y(i))?;
+            let columns: Vec<String> = self
+                .compared
+                .iter()
+                .flat_map(|comparison| {
+                    let stat = &comparison.stats[level];
+                    [
+                        Self::win_rate(stat),
+                        stat.best_time.map(|time| format!("{:.1}", time)).unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            self.draw_row(canvas, &columns.iter().map(String::as_str).collect::<Vec<&str>>(), Self::row_y(i))?;
+        }
+        Ok(())
+    }
 
+    // the time of every win of the chosen level, with its rolling average on top
+    fn draw_chart(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let Some(trend) = self.levels.get(self.chart_level).map(|level| &self.trends[level]) else {
+            return Ok(());
+        };
+        let top = Self::row_y(self.levels.len()) - 0.5 * consts::SCORES_SCREEN_SIZE.1 * 0.2;
+        let height = CHART_ROWS as f32 * consts::SCORES_SCREEN_SIZE.1 * 0.2;
+        let (left, width) = (consts::SCORES_SCREEN_SIZE.0 * 0.1, consts::SCORES_SCREEN_SIZE.0 * 0.85);
+        let slowest = trend.win_times.iter().copied().fold(0.0, f32::max);
+        let axis = Mesh::new_line(
+            ctx,
+            &[
+                Point2 { x: left, y: top },
+                Point2 { x: left, y: top + height },
+                Point2 { x: left + width, y: top + height },
+            ],
+            2.0,
+            colors().button,
+        )?;
+        canvas.draw(&axis, DrawParam::default());
         draw_text(
             canvas,
-            average_time,
-            (consts::SCORES_SCREEN_SIZE.0 * 0.90, y_pos),
-            0.7 * consts::QUAD_SIZE.1,
-            TextLayout::center(),
-            consts::BUTTON_TEXT_COLOR,
+            format!("{:.0}", slowest).as_str(),
+            (left - 0.01 * consts::SCORES_SCREEN_SIZE.0, top),
+            0.5 * consts::QUAD_SIZE.1,
+            TextLayout {
+                h_align: TextAlign::End,
+                v_align: TextAlign::Begin,
+            },
+            colors().text,
         )?;
-
+        if trend.win_times.len() < 2 || slowest <= 0.0 {
+            return draw_text(
+                canvas,
+                "not enough wins for a chart",
+                (left + 0.5 * width, top + 0.5 * height),
+                0.5 * consts::QUAD_SIZE.1,
+                TextLayout::center(),
+                colors().text,
+            );
+        }
+        let points = |times: &[f32]| -> Vec<Point2<f32>> {
+            let step = width / (times.len() - 1) as f32;
+            times
+                .iter()
+                .enumerate()
+                .map(|(i, time)| Point2 {
+                    x: left + i as f32 * step,
+                    y: top + height * (1.0 - time / slowest),
+                })
+                .collect()
+        };
+        let times = Mesh::new_line(ctx, &points(&trend.win_times), 2.0, colors().button_hovered)?;
+        canvas.draw(&times, DrawParam::default());
+        let average = Mesh::new_line(ctx, &points(&trend.rolling_averages()), 4.0, colors().flag)?;
+        canvas.draw(&average, DrawParam::default());
         Ok(())
     }
 
-    pub fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         if let Some(error) = &self.error {
             draw_text(
                 canvas,
                 error,
                 (
                     0.5 * consts::SCORES_SCREEN_SIZE.0,
-                    0.5 * consts::SCORES_SCREEN_SIZE.1,
+                    0.5 * self.size().1,
                 ),
                 consts::BUTTON_SIZE.1,
                 TextLayout::center(),
-                consts::BUTTON_TEXT_COLOR,
+                colors().text,
             )?;
         } else {
-            self.draw_headers(
+            draw_text(
                 canvas,
-                "P",
-                "W",
-                "L",
-                "A",
-                "W%",
-                "BT",
-                "AT",
+                self.safe_zone.label(),
+                (
+                    consts::SCORES_SCREEN_SIZE.0 * 0.05,
+                    consts::SCORES_SCREEN_SIZE.1 * 0.1,
+                ),
+                0.7 * consts::QUAD_SIZE.1,
+                TextLayout {
+                    h_align: TextAlign::Begin,
+                    v_align: TextAlign::Middle,
+                },
+                colors().text,
+            )?;
+            if self.show_trends {
+                return self.draw_trends(ctx, canvas);
+            }
+            if self.profile.is_none() {
+                return self.draw_comparison(canvas);
+            }
+            self.draw_row(
+                canvas,
+                &["P", "W", "L", "A", "W%", "AW", "BT", "AT"],
                 consts::SCORES_SCREEN_SIZE.1 * 0.1,
             )?;
             draw_text(
                 canvas,
-                "Total",
+                self.total_label(),
                 (
                     consts::SCORES_SCREEN_SIZE.0 * 0.05,
                     consts::SCORES_SCREEN_SIZE.1 * 0.25,
@@ -195,60 +618,231 @@ impl Scores {
                     h_align: TextAlign::Begin,
                     v_align: TextAlign::Middle,
                 },
-                consts::BUTTON_TEXT_COLOR,
+                colors().text,
             )?;
 
-            self.draw_headers(
+            self.draw_row(
                 canvas,
-                self.total_stats.played.to_string().as_str(),
-                self.total_stats.won.to_string().as_str(),
-                self.total_stats.lost.to_string().as_str(),
-                self.total_stats.abandoned.to_string().as_str(),
-                format!(
-                    "{:.1}%",
-                    100.0 * (self.total_stats.won as f32) / (self.total_stats.played as f32)
-                )
-                .as_str(),
-                "",
-                "",
+                &[
+                    self.total_stats.played.to_string().as_str(),
+                    self.total_stats.won.to_string().as_str(),
+                    self.total_stats.lost.to_string().as_str(),
+                    self.total_stats.abandoned.to_string().as_str(),
+                    Self::win_rate(&self.total_stats).as_str(),
+                    self.total_stats.assisted.to_string().as_str(),
+                    "",
+                    "",
+                ],
                 consts::SCORES_SCREEN_SIZE.1 * 0.25,
             )?;
 
-            for (i, level) in LEVELS.iter().enumerate() {
-                draw_text(
-                    canvas,
-                    &level.level_info().name,
-                    (
-                        consts::SCORES_SCREEN_SIZE.0 * 0.05,
-                        consts::SCORES_SCREEN_SIZE.1 * (0.45 + (i as f32) * 0.2),
-                    ),
-                    0.7 * consts::QUAD_SIZE.1,
-                    TextLayout {
-                        h_align: TextAlign::Begin,
-                        v_align: TextAlign::Middle,
-                    },
-                    consts::BUTTON_TEXT_COLOR,
-                )?;
+            for (i, level) in self.levels.iter().enumerate() {
+                self.draw_label(canvas, &level.label(), Self::row_y(i))?;
                 let stat = &self.stats[level];
-                self.draw_headers(
+                self.draw_row(
                     canvas,
-                    stat.played.to_string().as_str(),
-                    stat.won.to_string().as_str(),
-                    stat.lost.to_string().as_str(),
-                    stat.abandoned.to_string().as_str(),
-                    format!("{:.1}%", 100.0 * (stat.won as f32) / (stat.played as f32)).as_str(),
-                    stat.best_time
-                        .map(|f| format!("{:.1}", f))
-                        .unwrap_or("".to_string())
-                        .as_str(),
-                    stat.average_time
-                        .map(|f| format!("{:.1}", f))
-                        .unwrap_or("".to_string())
-                        .as_str(),
-                    consts::SCORES_SCREEN_SIZE.1 * (0.45 + (i as f32) * 0.2),
+                    &[
+                        stat.played.to_string().as_str(),
+                        stat.won.to_string().as_str(),
+                        stat.lost.to_string().as_str(),
+                        stat.abandoned.to_string().as_str(),
+                        Self::win_rate(stat).as_str(),
+                        stat.assisted.to_string().as_str(),
+                        stat.best_time
+                            .map(|f| format!("{:.1}", f))
+                            .unwrap_or("".to_string())
+                            .as_str(),
+                        stat.average_time
+                            .map(|f| format!("{:.1}", f))
+                            .unwrap_or("".to_string())
+                            .as_str(),
+                    ],
+                    Self::row_y(i),
                 )?;
             }
+            self.draw_best_games(canvas)?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::config::DEFAULT_PROFILE;
+    use crate::minezweeper::store::MemoryStore;
+    use chrono::{Duration, Local};
+
+    fn score(level: Level, game_state: GameState, time: f32, hints: usize, safe_zone: SafeZone) -> Score {
+        Score {
+            date_time: Local::now() + Duration::seconds(time as i64),
+            safe_zone,
+            hints,
+            ..Score::for_test(level, game_state, time)
+        }
+    }
+
+    fn store(scores: &[Score]) -> MemoryStore {
+        let mut store = MemoryStore::default();
+        store.save_all(scores).unwrap();
+        store
+    }
+
+    #[test]
+    fn empty_store() {
+        let scores = Scores::standard(&MemoryStore::default(), SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        assert!(scores.error.is_none());
+        assert_eq!(scores.total_stats.played, 0);
+        assert_eq!(Scores::win_rate(&scores.total_stats), "-");
+        assert_eq!(Scores::win_rate(&scores.stats[&Level::Easy]), "-");
+        assert_eq!(scores.levels, LEVELS.to_vec());
+        assert!(scores.best_games.is_empty());
+    }
+
+    #[test]
+    fn statistics_per_level() {
+        let store = store(&[
+            score(Level::Easy, GameState::Won, 10.0, 0, SafeZone::Opening),
+            score(Level::Easy, GameState::Won, 20.0, 0, SafeZone::Opening),
+            score(Level::Easy, GameState::Won, 5.0, 1, SafeZone::Opening),
+            score(Level::Easy, GameState::Lost, 3.0, 0, SafeZone::Opening),
+            score(Level::Hard, GameState::Abandoned, 30.0, 0, SafeZone::Opening),
+        ]);
+        let scores = Scores::standard(&store, SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+
+        let easy = &scores.stats[&Level::Easy];
+        assert_eq!((easy.played, easy.won, easy.lost, easy.abandoned, easy.assisted), (4, 3, 1, 0, 1));
+        // the assisted win is faster but does not count
+        assert_eq!(easy.best_time, Some(10.0));
+        assert_eq!(easy.average_time, Some(15.0));
+
+        let hard = &scores.stats[&Level::Hard];
+        assert_eq!((hard.played, hard.abandoned), (1, 1));
+        assert_eq!(hard.best_time, None);
+
+        let total = &scores.total_stats;
+        assert_eq!((total.played, total.won, total.lost, total.abandoned, total.assisted), (5, 3, 1, 1, 1));
+    }
+
+    #[test]
+    fn other_safe_zones_are_left_out() {
+        let store = store(&[
+            score(Level::Medium, GameState::Won, 50.0, 0, SafeZone::Opening),
+            score(Level::Medium, GameState::Won, 40.0, 0, SafeZone::Cell),
+        ]);
+        let scores = Scores::standard(&store, SafeZone::Cell, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        assert_eq!(scores.total_stats.played, 1);
+        assert_eq!(scores.stats[&Level::Medium].best_time, Some(40.0));
+    }
+
+    #[test]
+    fn custom_levels_by_most_played() {
+        let custom = |width| Level::Custom { width, height: 10, mines: 10 };
+        let mut games = Vec::new();
+        for width in 10..10 + CUSTOM_ROWS + 1 {
+            for _ in 10..=width {
+                games.push(score(custom(width), GameState::Lost, 1.0, 0, SafeZone::Opening));
+            }
+        }
+        let scores = Scores::standard(&store(&games), SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        let shown: Vec<Level> = scores.levels[LEVELS.len()..].to_vec();
+        let expected: Vec<Level> = (11..11 + CUSTOM_ROWS).rev().map(custom).collect();
+        assert_eq!(shown, expected);
+    }
+
+    #[test]
+    fn best_games_are_sorted_and_unassisted() {
+        let mut games: Vec<Score> = (1..=BEST_GAMES + 2)
+            .map(|time| score(Level::Easy, GameState::Won, time as f32, 0, SafeZone::Opening))
+            .collect();
+        games.push(score(Level::Easy, GameState::Won, 0.5, 1, SafeZone::Opening));
+        let scores = Scores::standard(&store(&games), SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        let times: Vec<f32> = scores.best_games.iter().map(|score| score.time).collect();
+        assert_eq!(times, (1..=BEST_GAMES).map(|time| time as f32).collect::<Vec<f32>>());
+    }
+
+    #[test]
+    fn streaks() {
+        let outcomes = [
+            GameState::Won,
+            GameState::Won,
+            GameState::Lost,
+            GameState::Won,
+            GameState::Won,
+            GameState::Won,
+            GameState::Abandoned,
+            GameState::Won,
+        ];
+        let games: Vec<Score> = outcomes
+            .iter()
+            .enumerate()
+            .map(|(i, outcome)| score(Level::Easy, *outcome, i as f32, 0, SafeZone::Opening))
+            .collect();
+        let trend = Trend::from_scores(games.iter());
+        assert_eq!((trend.current_streak, trend.longest_streak), (1, 3));
+        assert_eq!(trend.recent_win_rate, Some(75.0));
+    }
+
+    #[test]
+    fn recent_win_rate_and_rolling_average() {
+        let mut games: Vec<Score> = (0..RECENT_GAMES)
+            .map(|i| score(Level::Hard, GameState::Lost, i as f32, 0, SafeZone::Opening))
+            .collect();
+        games.extend((0..RECENT_GAMES).map(|i| score(Level::Hard, GameState::Won, (i + 1) as f32, 0, SafeZone::Opening)));
+        let trend = Trend::from_scores(games.iter());
+        assert_eq!(trend.recent_win_rate, Some(100.0));
+        assert_eq!(trend.current_streak, RECENT_GAMES);
+        let last: Vec<f32> = (RECENT_GAMES - ROLLING_WINS + 1..=RECENT_GAMES).map(|time| time as f32).collect();
+        assert_eq!(trend.rolling_average, Some(last.iter().sum::<f32>() / ROLLING_WINS as f32));
+        assert_eq!(trend.rolling_averages().len(), RECENT_GAMES);
+        assert_eq!(trend.rolling_averages()[0], 1.0);
+    }
+
+    #[test]
+    fn trends_follow_the_date_order() {
+        // saved out of order, the store gives no guarantee
+        let mut late = score(Level::Medium, GameState::Lost, 1.0, 0, SafeZone::Opening);
+        late.date_time += Duration::days(1);
+        let early = score(Level::Medium, GameState::Won, 1.0, 0, SafeZone::Opening);
+        let scores = Scores::standard(&store(&[late, early]), SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        assert_eq!(scores.trends[&Level::Medium].current_streak, 0);
+        assert_eq!(scores.trends[&Level::Medium].longest_streak, 1);
+        assert_eq!(scores.total_trend.longest_streak, 1);
+    }
+
+    #[test]
+    fn one_profile_at_a_time() {
+        let mut other = score(Level::Easy, GameState::Won, 5.0, 0, SafeZone::Opening);
+        other.profile = "alice".to_string();
+        let store = store(&[other, score(Level::Easy, GameState::Lost, 8.0, 0, SafeZone::Opening)]);
+
+        let own = Scores::standard(&store, SafeZone::Opening, SortKey::Time, Some(DEFAULT_PROFILE.to_string()));
+        assert_eq!((own.total_stats.played, own.total_stats.won), (1, 0));
+        assert!(own.compared.is_empty());
+
+        let alice = Scores::standard(&store, SafeZone::Opening, SortKey::Time, Some("alice".to_string()));
+        assert_eq!(alice.stats[&Level::Easy].best_time, Some(5.0));
+    }
+
+    #[test]
+    fn profiles_side_by_side() {
+        let mut games = vec![score(Level::Hard, GameState::Lost, 1.0, 0, SafeZone::Opening)];
+        for (time, profile) in [(3.0, "bob"), (2.0, "alice"), (4.0, "alice")] {
+            let mut game = score(Level::Medium, GameState::Won, time, 0, SafeZone::Opening);
+            game.profile = profile.to_string();
+            games.push(game);
+        }
+        let scores = Scores::standard(&store(&games), SafeZone::Opening, SortKey::Time, None);
+        assert_eq!(scores.total_stats.played, 4);
+        assert_eq!(scores.rows(), LEVELS.len());
+
+        // the configured default profile comes first, the others in the order they first played
+        let profiles: Vec<&str> = scores.compared.iter().map(|comparison| comparison.profile.as_str()).collect();
+        assert_eq!(profiles, vec![DEFAULT_PROFILE, "alice", "bob"]);
+        assert_eq!(scores.compared[0].stats[&Level::Hard].lost, 1);
+        assert_eq!(scores.compared[1].stats[&Level::Medium].best_time, Some(2.0));
+        assert_eq!(scores.compared[1].total.played, 2);
+        assert_eq!(Scores::win_rate(&scores.compared[0].stats[&Level::Medium]), "-");
+        assert_eq!(scores.next_profile(), Some(DEFAULT_PROFILE.to_string()));
+    }
+}
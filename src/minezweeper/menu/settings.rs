@@ -1,15 +1,20 @@
-use ggez::graphics::{self, Canvas};
+use ggez::graphics::{self, Canvas, TextLayout};
 use ggez::{Context, GameResult};
 use super::buttons::Button;
 use crate::consts;
+use crate::minezweeper::{config::colors, draw_text};
 
 pub enum SettingSelected {
-    Scores, Controls, None
+    Scores, Controls, Export, Import, None
 }
 
 pub struct Settings {
     scores_button: Button,
     controls_button: Button,
+    export_button: Button,
+    import_button: Button,
+    // the outcome of the last export or import
+    message: Option<String>,
 }
 
 impl Settings {
@@ -18,7 +23,7 @@ impl Settings {
 
         let (button_width, button_height) = consts::BUTTON_SIZE;
         let horizontal_margin = 0.5 * (consts::SETTINGS_SCREEN_SIZE.0 - button_width);
-        let vertical_margin = 0.33 * (consts::SETTINGS_SCREEN_SIZE.1 - 2.0 * button_height);
+        let vertical_margin = 0.2 * (consts::SETTINGS_SCREEN_SIZE.1 - 4.0 * button_height);
         Settings {
             scores_button: Button::new(
                 "Scores".to_string(),
@@ -34,12 +39,47 @@ impl Settings {
                     button_width, button_height,
                 )
             ),
+            export_button: Button::new(
+                "Export".to_string(),
+                graphics::Rect::new(
+                    horizontal_margin, 3.0 * vertical_margin + 2.0 * button_height,
+                    button_width, button_height,
+                )
+            ),
+            import_button: Button::new(
+                "Import".to_string(),
+                graphics::Rect::new(
+                    horizontal_margin, 4.0 * vertical_margin + 3.0 * button_height,
+                    button_width, button_height,
+                )
+            ),
+            message: None,
         }
     }
 
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         self.scores_button.draw(ctx, canvas, graphics::DrawParam::default())?;
         self.controls_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.export_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        self.import_button.draw(ctx, canvas, graphics::DrawParam::default())?;
+        if let Some(message) = &self.message {
+            let vertical_margin = 0.2 * (consts::SETTINGS_SCREEN_SIZE.1 - 4.0 * consts::BUTTON_SIZE.1);
+            draw_text(
+                canvas,
+                message.as_str(),
+                (
+                    0.5 * consts::SETTINGS_SCREEN_SIZE.0,
+                    consts::SETTINGS_SCREEN_SIZE.1 - 0.5 * vertical_margin,
+                ),
+                0.4 * vertical_margin,
+                TextLayout::center(),
+                colors().text,
+            )?;
+        }
         Ok(())
     }
 
@@ -50,6 +90,8 @@ impl Settings {
     ) {
         self.scores_button.clicked = self.scores_button.point_inside(x, y);
         self.controls_button.clicked = self.controls_button.point_inside(x, y);
+        self.export_button.clicked = self.export_button.point_inside(x, y);
+        self.import_button.clicked = self.import_button.point_inside(x, y);
     }
 
     pub fn mouse_button_up_event(
@@ -63,6 +105,12 @@ impl Settings {
         else if self.controls_button.point_inside(x, y) {
             return SettingSelected::Controls;
         }
+        else if self.export_button.point_inside(x, y) {
+            return SettingSelected::Export;
+        }
+        else if self.import_button.point_inside(x, y) {
+            return SettingSelected::Import;
+        }
         SettingSelected::None
     }
 
@@ -79,5 +127,13 @@ impl Settings {
         if !self.controls_button.hovered {
             self.controls_button.clicked = false
         }
+        self.export_button.hovered = self.export_button.point_inside(x, y);
+        if !self.export_button.hovered {
+            self.export_button.clicked = false
+        }
+        self.import_button.hovered = self.import_button.point_inside(x, y);
+        if !self.import_button.hovered {
+            self.import_button.clicked = false
+        }
     }
 }
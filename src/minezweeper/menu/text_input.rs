@@ -0,0 +1,93 @@
+use crate::minezweeper::{config::colors, draw_text};
+use ggez::graphics::{Canvas, DrawMode, DrawParam, Mesh, Rect, TextAlign, TextLayout};
+use ggez::mint::Point2;
+use ggez::{Context, GameResult};
+
+// a text field that only accepts digits, focused by clicking on it
+pub struct TextInput {
+    label: String,
+    text: String,
+    rect: Rect,
+    max_length: usize,
+    pub hovered: bool,
+    pub focused: bool,
+}
+
+impl TextInput {
+    pub fn new(label: String, rect: Rect, max_length: usize) -> TextInput {
+        TextInput {
+            label,
+            text: String::new(),
+            rect,
+            max_length,
+            hovered: false,
+            focused: false,
+        }
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        param: impl Into<DrawParam>,
+    ) -> GameResult {
+        let color = if self.focused {
+            colors().button_clicked
+        } else if self.hovered {
+            colors().button_hovered
+        } else {
+            colors().button
+        };
+        let rectangle = Mesh::new_rounded_rectangle(
+            ctx,
+            DrawMode::fill(),
+            self.rect,
+            self.rect.h * 0.2,
+            color,
+        )?;
+        canvas.draw(&rectangle, param);
+
+        let text = if self.text.is_empty() && !self.focused {
+            self.label.clone()
+        } else if self.focused {
+            format!("{}: {}_", self.label, self.text)
+        } else {
+            format!("{}: {}", self.label, self.text)
+        };
+
+        draw_text(
+            canvas,
+            text.as_str(),
+            (
+                self.rect.left() + 0.2 * self.rect.h,
+                self.rect.top() + 0.5 * self.rect.h,
+            ),
+            0.6 * self.rect.h,
+            TextLayout {
+                h_align: TextAlign::Begin,
+                v_align: TextAlign::Middle,
+            },
+            colors().text,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn point_inside(&self, x: f32, y: f32) -> bool {
+        self.rect.contains(Point2 { x, y })
+    }
+
+    pub fn push(&mut self, character: char) {
+        if self.focused && character.is_ascii_digit() && self.text.len() < self.max_length {
+            self.text.push(character);
+        }
+    }
+
+    pub fn pop(&mut self) -> bool {
+        self.focused && self.text.pop().is_some()
+    }
+
+    pub fn value(&self) -> Option<u64> {
+        self.text.parse().ok()
+    }
+}
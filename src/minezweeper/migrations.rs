@@ -0,0 +1,280 @@
+use rusqlite::{Connection, Transaction};
+use std::error::Error;
+
+// each step brings the schema up by one version, the version is kept in sqlite's user_version.
+// databases written before versioning are at version 0 but may already have some of the columns,
+// so every step has to be safe to run on a table that is partially up to date
+const MIGRATIONS: &[fn(&Transaction) -> rusqlite::Result<()>] = &[
+    create_score_table,
+    add_seed,
+    add_safe_zone,
+    add_hints,
+    add_custom_size,
+    add_replay,
+    add_metrics,
+    add_player,
+    add_profile,
+];
+
+pub const VERSION: usize = MIGRATIONS.len();
+
+fn add_missing_column(tx: &Transaction, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists = tx
+        .prepare("SELECT 1 FROM pragma_table_info('score') WHERE name = ?1")?
+        .exists([column])?;
+    if !exists {
+        tx.execute(&format!("ALTER TABLE score ADD COLUMN {} {}", column, definition), ())?;
+    }
+    Ok(())
+}
+
+fn create_score_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS score (
+            id   INTEGER PRIMARY KEY,
+            level TEXT NOT NULL,
+            game_state TEXT NOT NULL,
+            time REAL NOT NULL,
+            date_time TEXT NOT NULL
+        )",
+        (),
+    )?;
+    Ok(())
+}
+
+// seeds are stored as their i64 bit pattern, sqlite has no unsigned integers
+fn add_seed(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "seed", "INTEGER")
+}
+
+fn add_safe_zone(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "safe_zone", "TEXT NOT NULL DEFAULT 'None'")
+}
+
+fn add_hints(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "hints", "INTEGER NOT NULL DEFAULT 0")
+}
+
+fn add_custom_size(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "width", "INTEGER")?;
+    add_missing_column(tx, "height", "INTEGER")?;
+    add_missing_column(tx, "mines", "INTEGER")
+}
+
+// the moves of the game as json, only kept in the database
+fn add_replay(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "replay", "TEXT")
+}
+
+fn add_metrics(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "bbbv", "INTEGER")?;
+    add_missing_column(tx, "bbbv_per_second", "REAL")?;
+    add_missing_column(tx, "left_clicks", "INTEGER")?;
+    add_missing_column(tx, "right_clicks", "INTEGER")?;
+    add_missing_column(tx, "chord_clicks", "INTEGER")?;
+    add_missing_column(tx, "clicks", "INTEGER")?;
+    add_missing_column(tx, "efficiency", "REAL")
+}
+
+fn add_player(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "player", "TEXT")
+}
+
+// the name of the profile the game was played on, the same as config::DEFAULT_PROFILE for older games
+fn add_profile(tx: &Transaction) -> rusqlite::Result<()> {
+    add_missing_column(tx, "profile", "TEXT NOT NULL DEFAULT 'default'")
+}
+
+pub fn version(con: &Connection) -> rusqlite::Result<usize> {
+    con.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+// a failing step is rolled back on its own, the steps before it stay applied
+pub fn migrate(con: &mut Connection) -> Result<(), Box<dyn Error>> {
+    let current = version(con)?;
+    if current > VERSION {
+        return Err(format!(
+            "Scores database is at version {}, this build only knows up to {}",
+            current, VERSION
+        )
+        .into());
+    }
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = con.transaction()?;
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", version + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Local};
+    use crate::minezweeper::config::DEFAULT_PROFILE;
+    use crate::minezweeper::game::{GameState, SafeZone};
+    use crate::minezweeper::settings::Score;
+    use crate::minezweeper::Level;
+
+    const SELECT: &str = "SELECT level, game_state, time, date_time, seed, safe_zone, hints, width, height, mines, replay,
+        bbbv, bbbv_per_second, left_clicks, right_clicks, chord_clicks, efficiency, player, profile FROM score";
+
+    fn scores(con: &Connection) -> Vec<Score> {
+        let mut stmt = con.prepare(SELECT).unwrap();
+        let rows = stmt.query_map([], Score::from_row).unwrap();
+        rows.map(|score| score.unwrap().unwrap()).collect()
+    }
+
+    fn columns(con: &Connection) -> Vec<String> {
+        let mut stmt = con.prepare("SELECT name FROM pragma_table_info('score')").unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.map(|name| name.unwrap()).collect()
+    }
+
+    // opens a database as an older release left it, before versioning
+    fn historical(schema: &str, insert: &str) -> Connection {
+        let con = Connection::open_in_memory().unwrap();
+        con.execute(schema, ()).unwrap();
+        con.execute(insert, ()).unwrap();
+        con
+    }
+
+    #[test]
+    fn new_database_gets_every_step() {
+        let mut con = Connection::open_in_memory().unwrap();
+        migrate(&mut con).unwrap();
+        assert_eq!(version(&con).unwrap(), VERSION);
+        assert_eq!(columns(&con).len(), 21);
+        assert!(scores(&con).is_empty());
+    }
+
+    #[test]
+    fn migrating_twice_changes_nothing() {
+        let mut con = Connection::open_in_memory().unwrap();
+        migrate(&mut con).unwrap();
+        let before = columns(&con);
+        migrate(&mut con).unwrap();
+        assert_eq!(columns(&con), before);
+        assert_eq!(version(&con).unwrap(), VERSION);
+    }
+
+    #[test]
+    fn original_schema() {
+        let mut con = historical(
+            "CREATE TABLE score (
+                id   INTEGER PRIMARY KEY,
+                level TEXT NOT NULL,
+                game_state TEXT NOT NULL,
+                time REAL NOT NULL,
+                date_time TEXT NOT NULL
+            )",
+            "INSERT INTO score (level, game_state, time, date_time)
+                VALUES ('Medium', 'Won', 42.5, '2023-05-01 12:00:00 +02:00')",
+        );
+        migrate(&mut con).unwrap();
+        let scores = scores(&con);
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].level, Level::Medium);
+        assert_eq!(scores[0].game_state, GameState::Won);
+        assert_eq!(scores[0].time, 42.5);
+        assert_eq!(scores[0].date_time, "2023-05-01 12:00:00 +02:00".parse::<DateTime<Local>>().unwrap());
+        assert_eq!(scores[0].seed, None);
+        assert_eq!(scores[0].safe_zone, SafeZone::None);
+        assert_eq!(scores[0].hints, 0);
+        assert!(scores[0].replay.is_none());
+        assert_eq!(scores[0].profile, DEFAULT_PROFILE);
+    }
+
+    #[test]
+    fn schema_with_seed_and_safe_zone() {
+        let mut con = historical(
+            "CREATE TABLE score (
+                id   INTEGER PRIMARY KEY,
+                level TEXT NOT NULL,
+                game_state TEXT NOT NULL,
+                time REAL NOT NULL,
+                date_time TEXT NOT NULL,
+                seed INTEGER,
+                safe_zone TEXT NOT NULL DEFAULT 'None'
+            )",
+            "INSERT INTO score (level, game_state, time, date_time, seed, safe_zone)
+                VALUES ('Hard', 'Lost', 7.0, '2023-06-01 08:30:00 +00:00', -1, 'Opening')",
+        );
+        migrate(&mut con).unwrap();
+        let scores = scores(&con);
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].level, Level::Hard);
+        assert_eq!(scores[0].game_state, GameState::Lost);
+        assert_eq!(scores[0].seed, Some(u64::MAX));
+        assert_eq!(scores[0].safe_zone, SafeZone::Opening);
+        assert_eq!(scores[0].hints, 0);
+    }
+
+    #[test]
+    fn schema_with_custom_size() {
+        let mut con = historical(
+            "CREATE TABLE score (
+                id   INTEGER PRIMARY KEY,
+                level TEXT NOT NULL,
+                game_state TEXT NOT NULL,
+                time REAL NOT NULL,
+                date_time TEXT NOT NULL,
+                seed INTEGER,
+                safe_zone TEXT NOT NULL DEFAULT 'None',
+                hints INTEGER NOT NULL DEFAULT 0,
+                width INTEGER,
+                height INTEGER,
+                mines INTEGER
+            )",
+            "INSERT INTO score (level, game_state, time, date_time, seed, safe_zone, hints, width, height, mines)
+                VALUES ('Custom', 'Won', 99.0, '2023-07-01 20:00:00 +00:00', 5, 'Cell', 2, 20, 10, 30)",
+        );
+        migrate(&mut con).unwrap();
+        let scores = scores(&con);
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].level, Level::Custom { width: 20, height: 10, mines: 30 });
+        assert_eq!(scores[0].seed, Some(5));
+        assert_eq!(scores[0].safe_zone, SafeZone::Cell);
+        assert_eq!(scores[0].hints, 2);
+        assert!(scores[0].bbbv.is_none());
+    }
+
+    #[test]
+    fn partially_upgraded_schema() {
+        // written by builds that added columns as needed, before user_version was set
+        let mut con = historical(
+            "CREATE TABLE score (
+                id   INTEGER PRIMARY KEY,
+                level TEXT NOT NULL,
+                game_state TEXT NOT NULL,
+                time REAL NOT NULL,
+                date_time TEXT NOT NULL,
+                seed INTEGER,
+                safe_zone TEXT NOT NULL DEFAULT 'None',
+                hints INTEGER NOT NULL DEFAULT 0,
+                width INTEGER,
+                height INTEGER,
+                mines INTEGER,
+                replay TEXT,
+                bbbv INTEGER
+            )",
+            "INSERT INTO score (level, game_state, time, date_time, bbbv)
+                VALUES ('Easy', 'Won', 3.0, '2023-08-01 20:00:00 +00:00', 12)",
+        );
+        migrate(&mut con).unwrap();
+        assert_eq!(version(&con).unwrap(), VERSION);
+        let scores = scores(&con);
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].level, Level::Easy);
+        assert_eq!(scores[0].bbbv, Some(12));
+        assert!(scores[0].clicks.is_none());
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let mut con = Connection::open_in_memory().unwrap();
+        con.pragma_update(None, "user_version", VERSION + 1).unwrap();
+        assert!(migrate(&mut con).is_err());
+    }
+}
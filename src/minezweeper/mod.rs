@@ -1,16 +1,28 @@
+pub mod cli;
+pub mod config;
 mod game;
 mod menu;
+mod migrations;
+mod session;
 mod settings;
+pub mod store;
+pub mod transfer;
+pub mod tui;
 
 use crate::consts;
 use crate::minezweeper::{
-    game::{Game, GameState},
+    config::{colors, LevelConfig},
+    game::{replay::{Player, Replay}, Game, GameState, SafeZone},
+    menu::controls::ControlsScreen,
+    menu::history::History,
+    menu::leaderboard::Leaderboard,
+    menu::custom::{Custom, CustomSelected},
     menu::settings::{Settings, SettingSelected},
-    menu::scores::Scores,
+    menu::scores::{Scores, SortKey},
     menu::Menu,
     menu::Selected,
-    settings::Controls,
-    settings::Score,
+    session::Session,
+    store::ScoreStore,
 };
 use ggez::event::EventHandler;
 use ggez::graphics::{self, Canvas, Color, DrawParam, PxScale, Text, TextFragment, TextLayout};
@@ -20,19 +32,33 @@ use ggez::input::{
 };
 use ggez::mint::Point2;
 use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
+#[allow(clippy::large_enum_variant)]
 enum Screen {
     Menu(Menu),
     Game(Game),
     Settings(Settings),
     Scores(Scores),
+    Custom(Custom),
+    // the screen the replay was opened from, shown again when it is left
+    Replay(Player, Box<Screen>),
+    Controls(ControlsScreen),
+    History(History),
+    Leaderboard(Leaderboard),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Level {
     Easy,
     Medium,
     Hard,
+    Custom {
+        width: usize,
+        height: usize,
+        mines: usize,
+    },
 }
 
 pub fn draw_text(
@@ -57,72 +83,214 @@ pub fn draw_text(
     Ok(())
 }
 
+// the settings screen only has room for a line, the full report goes to the terminal
+fn export_scores(store: &dyn ScoreStore) -> String {
+    match transfer::export_all(store) {
+        Ok(dir) => {
+            println!("Scores exported to {}", dir.display());
+            "Exported to export.csv and export.json".to_string()
+        }
+        Err(err) => {
+            println!("Error exporting scores: {}", err);
+            "Export failed".to_string()
+        }
+    }
+}
+
+fn import_scores(store: &mut dyn ScoreStore) -> String {
+    match transfer::import_all(store) {
+        Ok(report) => {
+            println!("{}", report);
+            report.summary()
+        }
+        Err(err) => {
+            println!("Error importing scores: {}", err);
+            "Import failed".to_string()
+        }
+    }
+}
+
 pub struct LevelInfo {
     pub name: String,
     pub grid_size: (usize, usize),
     pub number_of_mines: usize,
 }
 
+impl LevelInfo {
+    fn from_config(name: &str, level: &LevelConfig) -> Self {
+        LevelInfo {
+            name: name.to_string(),
+            grid_size: (level.width, level.height),
+            number_of_mines: level.mines,
+        }
+    }
+}
+
 impl Level {
     pub fn level_info(&self) -> LevelInfo {
         match self {
-            Self::Easy => LevelInfo {
-                name: "Easy".to_string(),
-                grid_size: (9, 9),
-                number_of_mines: 10,
-            },
-            Self::Medium => LevelInfo {
-                name: "Medium".to_string(),
-                grid_size: (16, 16),
-                number_of_mines: 40,
-            },
-            Self::Hard => LevelInfo {
-                name: "Hard".to_string(),
-                grid_size: (30, 16),
-                number_of_mines: 99,
+            Self::Easy => LevelInfo::from_config("Easy", &config::get().levels.easy),
+            Self::Medium => LevelInfo::from_config("Medium", &config::get().levels.medium),
+            Self::Hard => LevelInfo::from_config("Hard", &config::get().levels.hard),
+            Self::Custom { width, height, mines } => LevelInfo {
+                name: "Custom".to_string(),
+                grid_size: (*width, *height),
+                number_of_mines: *mines,
             },
         }
     }
+
+    // custom levels are told apart by their size
+    pub fn label(&self) -> String {
+        match self {
+            Self::Custom { width, height, mines } => format!("{}x{} {}", width, height, mines),
+            _ => self.level_info().name,
+        }
+    }
+}
+
+// a level as typed on the command line, easy, medium, hard or WxH:M for a custom one
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "easy" => return Ok(Level::Easy),
+            "medium" => return Ok(Level::Medium),
+            "hard" => return Ok(Level::Hard),
+            _ => {}
+        }
+        let invalid = || format!("`{}` is not a level, use easy, medium, hard or WxH:M", text);
+        let (size, mines) = text.split_once(':').ok_or_else(invalid)?;
+        let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+        let number = |text: &str| text.trim().parse::<usize>().map_err(|_| invalid());
+        let (width, height, mines) = (number(width)?, number(height)?, number(mines)?);
+        Game::validate((width, height), mines).map_err(|err| err.to_string())?;
+        Ok(Level::Custom { width, height, mines })
+    }
+}
+
+// what is shown first, the menu unless a game or a replay was asked for on the command line
+pub enum Launch {
+    Menu,
+    Game(Level, Option<u64>),
+    Replay(Replay),
 }
 
 pub struct Minezweeper {
     screen: Screen,
-    controls: Controls,
-    started_level: Option<Level>,
+    session: Session,
 }
 
 impl Minezweeper {
-    pub fn new(_ctx: &mut Context) -> Minezweeper {
+    pub fn new(ctx: &mut Context, launch: Launch) -> GameResult<Minezweeper> {
         // Load/create resources such as images here.
-        Minezweeper {
-            screen: Screen::Menu(Menu::standard()),
-            controls: Controls::default(),
-            started_level: None,
+        let session = Session::new();
+        let mut minezweeper = Minezweeper {
+            screen: Screen::Menu(Menu::standard(session.safe_zone, session.generation, &session.profile)),
+            session,
+        };
+        match launch {
+            Launch::Menu => {}
+            Launch::Game(level, seed) => minezweeper.start_game(level, seed, ctx)?,
+            Launch::Replay(replay) => minezweeper.open_replay(replay, ctx)?,
+        }
+        Ok(minezweeper)
+    }
+
+    fn menu(&self) -> Screen {
+        Screen::Menu(Menu::standard(self.session.safe_zone, self.session.generation, &self.session.profile))
+    }
+
+    // the window has nowhere to show them, they go to the terminal it was started from
+    fn print_messages(&mut self) {
+        for message in self.session.take_messages() {
+            println!("{}", message);
+        }
+    }
+
+    // shrinks the cells of boards that would not fit on the monitor
+    fn quad_size(ctx: &Context, grid_size: (usize, usize)) -> (f32, f32) {
+        let (max_width, max_height) = match ctx.gfx.window().current_monitor() {
+            Some(monitor) => {
+                let size = monitor.size().to_logical::<f32>(monitor.scale_factor());
+                (0.9 * size.width, 0.85 * size.height)
+            }
+            None => (consts::SCORES_SCREEN_SIZE.0, consts::SCORES_SCREEN_SIZE.0 * 0.6),
+        };
+        let quad_size = consts::QUAD_SIZE.0
+            .min(max_width / grid_size.0 as f32)
+            .min(max_height / (grid_size.1 + 1) as f32);
+        (quad_size, quad_size)
+    }
+
+    fn start_game(&mut self, level: Level, seed: Option<u64>, ctx: &mut Context) -> GameResult {
+        match self.session.new_game(level, seed) {
+            Ok(game) => self.show_game(game, ctx),
+            Err(error) => {
+                if let Screen::Custom(custom) = &mut self.screen {
+                    custom.set_error(error.to_string());
+                }
+                Ok(())
+            }
         }
     }
 
-    fn start_game(&mut self, level: Level, ctx: &mut Context) -> GameResult {
-        self.started_level = Some(level);
-        let level_info = level.level_info();
-        let grid_size = level_info.grid_size;
+    // sizes the window to the board, returning the size of its cells
+    fn fit_board(ctx: &mut Context, grid_size: (usize, usize)) -> GameResult<(f32, f32)> {
+        let quad_size = Self::quad_size(ctx, grid_size);
         ctx.gfx.set_drawable_size(
-            grid_size.0 as f32 * consts::QUAD_SIZE.0,
-            consts::QUAD_SIZE.1 + grid_size.1 as f32 * consts::QUAD_SIZE.1,
+            grid_size.0 as f32 * quad_size.0,
+            quad_size.1 + grid_size.1 as f32 * quad_size.1,
         )?;
-        self.screen = Screen::Game(Game::new(grid_size, level_info.number_of_mines));
+        Ok(quad_size)
+    }
+
+    fn show_game(&mut self, mut game: Game, ctx: &mut Context) -> GameResult {
+        game.set_quad_size(Self::fit_board(ctx, game.get_shape())?);
+        self.screen = Screen::Game(game);
         Ok(())
     }
 
-    fn end_game(&mut self, game_state: GameState) {
-        if let Some(level) = self.started_level {
-            if let Screen::Game(game) = &self.screen {
-                if let Err(err) = Score::new(level, game_state, game.get_final_time()).save() {
-                    println!("Error writing score to file: {}", err);
-                } else {
-                    println!("Score written to file {:?}", game_state);
-                }
+    fn open_replay(&mut self, replay: Replay, ctx: &mut Context) -> GameResult {
+        let mut player = Player::new(replay);
+        player.set_quad_size(Self::fit_board(ctx, player.get_shape())?);
+        let menu = self.menu();
+        let from = std::mem::replace(&mut self.screen, menu);
+        self.screen = Screen::Replay(player, Box::new(from));
+        Ok(())
+    }
+
+    // scores and history come back with their filters and selection as they were left
+    fn leave_replay(&mut self, ctx: &mut Context) -> GameResult {
+        let menu = self.menu();
+        let from = match std::mem::replace(&mut self.screen, menu) {
+            Screen::Replay(_, from) => *from,
+            screen => screen,
+        };
+        let (width, height) = match &from {
+            Screen::Scores(scores) => scores.size(),
+            Screen::History(_) => consts::HISTORY_SCREEN_SIZE,
+            _ => consts::SCREEN_SIZE,
+        };
+        ctx.gfx.set_drawable_size(width, height)?;
+        self.screen = from;
+        Ok(())
+    }
+
+    fn continue_game(&mut self, ctx: &mut Context) -> GameResult {
+        match self.session.continue_game() {
+            Some(game) => self.show_game(game, ctx),
+            None => {
+                self.screen = self.menu();
+                Ok(())
             }
-            self.started_level = None;
+        }
+    }
+
+    fn leave_game(&mut self) {
+        if let Screen::Game(game) = &mut self.screen {
+            self.session.leave_game(game);
         }
     }
 
@@ -135,24 +303,85 @@ impl Minezweeper {
         Ok(())
     }
 
-    fn open_scores(&mut self, ctx: &mut Context) -> GameResult {
-         ctx.gfx.set_drawable_size(
-            consts::SCORES_SCREEN_SIZE.0,
-            consts::SCORES_SCREEN_SIZE.1,
+    fn open_scores(&mut self, safe_zone: SafeZone, sort_key: SortKey, profile: Option<String>, ctx: &mut Context) -> GameResult {
+        let scores = Scores::standard(&*self.session.store, safe_zone, sort_key, profile);
+        let (width, height) = scores.size();
+        ctx.gfx.set_drawable_size(width, height)?;
+        self.screen = Screen::Scores(scores);
+        Ok(())
+    }
+
+    fn open_history(&mut self, ctx: &mut Context) -> GameResult {
+        ctx.gfx.set_drawable_size(
+            consts::HISTORY_SCREEN_SIZE.0,
+            consts::HISTORY_SCREEN_SIZE.1,
         )?;
-        self.screen = Screen::Scores(Scores::standard());
+        self.screen = Screen::History(History::standard(&*self.session.store, &self.session.profile));
         Ok(())
     }
 
+    fn open_leaderboard(&mut self, safe_zone: SafeZone, ctx: &mut Context) -> GameResult {
+        ctx.gfx.set_drawable_size(
+            consts::LEADERBOARD_SCREEN_SIZE.0,
+            consts::LEADERBOARD_SCREEN_SIZE.1,
+        )?;
+        self.screen = Screen::Leaderboard(Leaderboard::standard(&*self.session.store, safe_zone, &self.session.profile));
+        Ok(())
+    }
+
+    fn open_controls(&mut self, ctx: &mut Context) -> GameResult {
+        ctx.gfx.set_drawable_size(
+            consts::CONTROLS_SCREEN_SIZE.0,
+            consts::CONTROLS_SCREEN_SIZE.1,
+        )?;
+        self.screen = Screen::Controls(ControlsScreen::standard(&self.session.profile));
+        Ok(())
+    }
+
+    fn open_custom(&mut self, seed: Option<u64>, ctx: &mut Context) -> GameResult {
+        ctx.gfx.set_drawable_size(
+            consts::CUSTOM_SCREEN_SIZE.0,
+            consts::CUSTOM_SCREEN_SIZE.1,
+        )?;
+        self.screen = Screen::Custom(Custom::standard(seed));
+        Ok(())
+    }
+
+    fn toggle_safe_zone(&mut self) {
+        self.session.toggle_safe_zone();
+        if let Screen::Menu(menu) = &mut self.screen {
+            menu.set_safe_zone(self.session.safe_zone);
+        }
+    }
+
+    fn toggle_generation(&mut self) {
+        self.session.toggle_generation();
+        if let Screen::Menu(menu) = &mut self.screen {
+            menu.set_generation(self.session.generation);
+        }
+    }
+
+    // rebuilt rather than relabelled, the saved game to continue is the profile's own
+    fn switch_profile(&mut self) {
+        if self.session.switch_profile() {
+            if let Screen::Menu(_) = &self.screen {
+                self.screen = self.menu();
+            }
+        }
+    }
 }
 
 impl EventHandler for Minezweeper {
     fn update(&mut self, _ctx: &mut Context) -> GameResult {
+        self.print_messages();
+        if let Screen::Replay(player, _) = &mut self.screen {
+            player.update();
+        }
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+        let mut canvas = graphics::Canvas::from_frame(ctx, colors().background);
 
         match &self.screen {
             Screen::Menu(menu) => {
@@ -167,6 +396,21 @@ impl EventHandler for Minezweeper {
             Screen::Scores(scores) => {
                 scores.draw(ctx, &mut canvas)?;
             }
+            Screen::Custom(custom) => {
+                custom.draw(ctx, &mut canvas)?;
+            }
+            Screen::Replay(player, _) => {
+                player.draw(ctx, &mut canvas)?;
+            }
+            Screen::Controls(controls) => {
+                controls.draw(ctx, &mut canvas, &self.session.controls)?;
+            }
+            Screen::History(history) => {
+                history.draw(ctx, &mut canvas)?;
+            }
+            Screen::Leaderboard(leaderboard) => {
+                leaderboard.draw(ctx, &mut canvas)?;
+            }
         }
         canvas.finish(ctx)
     }
@@ -188,7 +432,10 @@ impl EventHandler for Minezweeper {
             Screen::Settings(settings) => {
                 settings.mouse_button_down_event(x, y);
             }
-            Screen::Scores(_) => {}
+            Screen::Custom(custom) => {
+                custom.mouse_button_down_event(x, y);
+            }
+            Screen::Scores(_) | Screen::Replay(..) | Screen::Controls(_) | Screen::History(_) | Screen::Leaderboard(_) => {}
         }
         Ok(())
     }
@@ -196,29 +443,51 @@ impl EventHandler for Minezweeper {
     fn mouse_button_up_event(
         &mut self,
         ctx: &mut Context,
-        _button: MouseButton,
+        button: MouseButton,
         x: f32,
         y: f32,
     ) -> GameResult {
         match &mut self.screen {
             Screen::Menu(menu) => match menu.mouse_button_up_event(x, y) {
-                Selected::Level(level) => self.start_game(level, ctx)?,
+                Selected::Level(level) => {
+                    let seed = menu.seed();
+                    self.start_game(level, seed, ctx)?
+                }
+                Selected::Continue => self.continue_game(ctx)?,
+                Selected::Custom => {
+                    let seed = menu.seed();
+                    self.open_custom(seed, ctx)?
+                }
                 Selected::Settings => self.open_settings(ctx)?,
+                Selected::SafeZone => self.toggle_safe_zone(),
+                Selected::Generation => self.toggle_generation(),
+                Selected::Profile => self.switch_profile(),
                 Selected::None => {}
             },
             Screen::Game(game) => {
-                let game_state = game.mouse_button_up_event(x, y);
+                let game_state = game.mouse_button_up_event(button, x, y);
                 if game_state != GameState::Playing {
-                    self.end_game(game_state)
+                    self.session.end_game(game, game_state)
                 }
             }
             Screen::Settings(settings) => match settings.mouse_button_up_event(x, y) {
-                SettingSelected::Scores => self.open_scores(ctx)?,
-                SettingSelected::Controls => {}
+                SettingSelected::Scores => self.open_scores(self.session.safe_zone, SortKey::BbbvPerSecond, Some(self.session.profile.clone()), ctx)?,
+                SettingSelected::Controls => self.open_controls(ctx)?,
+                SettingSelected::Export => settings.set_message(export_scores(&*self.session.store)),
+                SettingSelected::Import => settings.set_message(import_scores(&mut *self.session.store)),
                 SettingSelected::None => {}
             }
+            Screen::Custom(custom) => match custom.mouse_button_up_event(x, y) {
+                CustomSelected::Play => {
+                    let (level, seed) = (custom.level(), custom.seed());
+                    self.start_game(level, seed, ctx)?
+                }
+                CustomSelected::None => {}
+            }
 
-            Screen::Scores(_) => {}
+            Screen::Controls(controls) => controls.mouse_button_up_event(x, y),
+            Screen::History(history) => history.mouse_button_up_event(x, y),
+            Screen::Scores(_) | Screen::Replay(..) | Screen::Leaderboard(_) => {}
         }
         Ok(())
     }
@@ -235,7 +504,29 @@ impl EventHandler for Minezweeper {
             Screen::Menu(menu) => menu.mouse_motion_event(x, y),
             Screen::Game(game) => game.mouse_motion_event(x, y),
             Screen::Settings(settings) => settings.mouse_motion_event(x, y),
-            Screen::Scores(_) => {}
+            Screen::Custom(custom) => custom.mouse_motion_event(x, y),
+            Screen::Controls(controls) => controls.mouse_motion_event(x, y),
+            Screen::History(history) => history.mouse_motion_event(x, y),
+            Screen::Scores(_) | Screen::Replay(..) | Screen::Leaderboard(_) => {}
+        }
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        match &mut self.screen {
+            Screen::Menu(menu) => menu.text_input_event(character),
+            Screen::Custom(custom) => custom.text_input_event(character),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // a game is paused as soon as the window is left for another
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) -> GameResult {
+        if let Screen::Game(game) = &mut self.screen {
+            if !gained {
+                game.pause();
+            }
         }
         Ok(())
     }
@@ -248,13 +539,25 @@ impl EventHandler for Minezweeper {
     }
 
     fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeat: bool) -> GameResult {
+        let capturing = matches!(&self.screen, Screen::Controls(controls) if controls.is_waiting());
         match &mut self.screen {
-            Screen::Menu(_) => {
+            Screen::Menu(menu) if menu.typing_seed() => {
+                if let Some(KeyCode::Back) = input.keycode {
+                    menu.erase_character();
+                }
+            }
+            Screen::Menu(menu) => {
+                let seed = menu.seed();
                 match input.keycode {
-                    Some(KeyCode::Key1) => self.start_game(Level::Easy, ctx)?,
-                    Some(KeyCode::Key2) => self.start_game(Level::Medium, ctx)?,
-                    Some(KeyCode::Key3) => self.start_game(Level::Hard, ctx)?,
+                    Some(KeyCode::Key1) => self.start_game(Level::Easy, seed, ctx)?,
+                    Some(KeyCode::Key2) => self.start_game(Level::Medium, seed, ctx)?,
+                    Some(KeyCode::Key3) => self.start_game(Level::Hard, seed, ctx)?,
+                    Some(KeyCode::C) => self.open_custom(seed, ctx)?,
+                    Some(KeyCode::R) => self.continue_game(ctx)?,
                     Some(KeyCode::S) => self.open_settings(ctx)?,
+                    Some(KeyCode::F) => self.toggle_safe_zone(),
+                    Some(KeyCode::G) => self.toggle_generation(),
+                    Some(KeyCode::P) => self.switch_profile(),
                     _ => {}
                 };
             }
@@ -262,41 +565,129 @@ impl EventHandler for Minezweeper {
                 Some(KeyCode::Back) => {
                     ctx.gfx
                         .set_drawable_size(consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1)?;
-                    self.end_game(GameState::Abandoned);
-                    self.screen = Screen::Menu(Menu::standard())
+                    self.leave_game();
+                    self.screen = self.menu()
+                }
+                // finished games can be watched again straight away
+                Some(KeyCode::R) if game.get_game_state() != GameState::Playing => {
+                    if let Some(replay) = game.replay() {
+                        self.open_replay(replay, ctx)?
+                    }
                 }
                 Some(keycode) => {
-                    let game_state = game.handle(self.controls.handle(keycode));
+                    let game_state = game.handle(self.session.controls.handle(keycode));
                     if game_state != GameState::Playing {
-                        self.end_game(game_state)
+                        self.session.end_game(game, game_state)
                     }
                 }
                 None => {}
             },
-            Screen::Settings(_) => match input.keycode {
-                Some(KeyCode::Key1) => self.open_scores(ctx)?,
-                Some(KeyCode::Key2) => {} 
+            Screen::Settings(settings) => match input.keycode {
+                Some(KeyCode::Key1) => self.open_scores(self.session.safe_zone, SortKey::BbbvPerSecond, Some(self.session.profile.clone()), ctx)?,
+                Some(KeyCode::Key2) => self.open_controls(ctx)?,
+                Some(KeyCode::Key3) => settings.set_message(export_scores(&*self.session.store)),
+                Some(KeyCode::Key4) => settings.set_message(import_scores(&mut *self.session.store)),
                 Some(KeyCode::Back) => {
                     ctx.gfx
                         .set_drawable_size(consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1)?;
-                    self.end_game(GameState::Abandoned);
-                    self.screen = Screen::Menu(Menu::standard())
+                    self.screen = self.menu()
                 }
                 Some(_) | None => {}
             },
-            Screen::Scores(_) => match input.keycode {
+            Screen::Scores(scores) => match input.keycode {
                 Some(KeyCode::Back) => {
                     ctx.gfx
                         .set_drawable_size(consts::SETTINGS_SCREEN_SIZE.0, consts::SETTINGS_SCREEN_SIZE.1)?;
                     self.screen = Screen::Settings(Settings::standard())
                 }
+                Some(KeyCode::F) => {
+                    let (safe_zone, sort_key) = (scores.safe_zone().next(&SafeZone::ALL), scores.sort_key());
+                    let profile = scores.profile().map(str::to_string);
+                    self.open_scores(safe_zone, sort_key, profile, ctx)?;
+                }
+                Some(KeyCode::Tab) => {
+                    let (safe_zone, sort_key) = (scores.safe_zone(), scores.sort_key().next());
+                    let profile = scores.profile().map(str::to_string);
+                    self.open_scores(safe_zone, sort_key, profile, ctx)?;
+                }
+                Some(KeyCode::P) => {
+                    let (safe_zone, sort_key, profile) = (scores.safe_zone(), scores.sort_key(), scores.next_profile());
+                    self.open_scores(safe_zone, sort_key, profile, ctx)?;
+                }
+                Some(KeyCode::R) => {
+                    if let Some(replay) = scores.latest_replay() {
+                        self.open_replay(replay, ctx)?
+                    }
+                }
+                Some(KeyCode::H) => self.open_history(ctx)?,
+                Some(KeyCode::T) => {
+                    scores.toggle_trends();
+                    let (width, height) = scores.size();
+                    ctx.gfx.set_drawable_size(width, height)?;
+                }
+                Some(KeyCode::B) => {
+                    let safe_zone = scores.safe_zone();
+                    self.open_leaderboard(safe_zone, ctx)?
+                }
+                Some(keycode) => scores.key_down_event(keycode),
+                None => {}
+            },
+            Screen::Leaderboard(leaderboard) => match input.keycode {
+                Some(KeyCode::Back) => self.open_scores(self.session.safe_zone, SortKey::BbbvPerSecond, Some(self.session.profile.clone()), ctx)?,
+                Some(keycode) => leaderboard.key_down_event(keycode),
+                None => {}
+            },
+            Screen::History(history) => match input.keycode {
+                Some(KeyCode::Back) if history.close_details() => {}
+                Some(KeyCode::Back) => self.open_scores(self.session.safe_zone, SortKey::BbbvPerSecond, Some(self.session.profile.clone()), ctx)?,
+                Some(KeyCode::R) => {
+                    if let Some(replay) = history.selected_replay() {
+                        self.open_replay(replay, ctx)?
+                    }
+                }
+                Some(keycode) => history.key_down_event(keycode),
+                None => {}
+            },
+            Screen::Controls(controls) => match input.keycode {
+                Some(keycode) if controls.key_down_event(keycode, &mut self.session.controls) => {}
+                Some(KeyCode::Back) => self.open_settings(ctx)?,
+                Some(_) | None => {}
+            },
+            Screen::Replay(player, _) => match input.keycode {
+                Some(KeyCode::Back) => self.leave_replay(ctx)?,
+                Some(KeyCode::Space) => player.toggle_pause(),
+                Some(KeyCode::Right) => player.step(),
+                Some(KeyCode::Up) => player.faster(),
+                Some(KeyCode::Down) => player.slower(),
+                Some(KeyCode::Return) => player.restart(),
+                Some(_) | None => {}
+            },
+            Screen::Custom(custom) => match input.keycode {
+                // erases the focused field, and leaves once it is empty
+                Some(KeyCode::Back) => {
+                    if !custom.erase_character() {
+                        ctx.gfx
+                            .set_drawable_size(consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1)?;
+                        self.screen = self.menu()
+                    }
+                }
+                Some(KeyCode::Tab) => custom.focus_next(),
+                Some(KeyCode::Return) => {
+                    let (level, seed) = (custom.level(), custom.seed());
+                    self.start_game(level, seed, ctx)?
+                }
                 Some(_) | None => {}
             },
-
         }
-        if let Some(KeyCode::Escape) = input.keycode {
+        if input.keycode == Some(KeyCode::Escape) && !capturing {
             ctx.request_quit();
         }
         Ok(())
     }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> GameResult<bool> {
+        self.leave_game();
+        self.print_messages();
+        Ok(false)
+    }
 }
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crate::minezweeper::{
+    config,
+    game::{Game, GameState, Generation, GridError, SafeZone},
+    menu::leaderboard,
+    settings::{Controls, SavedGame, Score},
+    store::{self, ScoreStore},
+    Level,
+};
+
+// what the window and the terminal share between games, the profile, its preferences and the scores
+pub struct Session {
+    pub profile: String,
+    pub controls: Controls,
+    // bindings and preferences of the other profiles as they were left during this run,
+    // the config is only read at start
+    others: HashMap<String, (Controls, SafeZone, Generation)>,
+    pub safe_zone: SafeZone,
+    pub generation: Generation,
+    pub store: Box<dyn ScoreStore>,
+    // set while a game is played that still has to be scored
+    started_level: Option<Level>,
+    // what went on, each frontend shows them its own way
+    messages: Vec<String>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let profile = config::get().profile.clone();
+        Session {
+            controls: config::controls(&profile),
+            others: HashMap::new(),
+            safe_zone: config::safe_zone(&profile),
+            generation: config::generation(&profile),
+            profile,
+            store: store::open(),
+            started_level: None,
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+
+    pub fn has_saved_game(&self) -> bool {
+        SavedGame::exists(&self.profile)
+    }
+
+    // unreadable saved games are reported when they are continued
+    pub fn saved_level(&self) -> Option<Level> {
+        SavedGame::load(&self.profile).ok().flatten().map(|saved_game| saved_game.level)
+    }
+
+    pub fn new_game(&mut self, level: Level, seed: Option<u64>) -> Result<Game, GridError> {
+        let level_info = level.level_info();
+        let game = Game::new(
+            level_info.grid_size,
+            level_info.number_of_mines,
+            seed,
+            self.safe_zone,
+            self.generation,
+        )?;
+        // only one game is kept, starting another gives up on the saved one
+        self.abandon_saved_game();
+        self.started_level = Some(level);
+        Ok(game)
+    }
+
+    pub fn continue_game(&mut self) -> Option<Game> {
+        match SavedGame::take(&self.profile) {
+            Ok(Some(saved_game)) => {
+                self.started_level = Some(saved_game.level);
+                Some(Game::restore(saved_game.snapshot))
+            }
+            Ok(None) => None,
+            Err(err) => {
+                self.messages.push(format!("Error reading saved game: {}", err));
+                None
+            }
+        }
+    }
+
+    fn abandon_saved_game(&mut self) {
+        match SavedGame::take(&self.profile) {
+            Ok(Some(saved_game)) => {
+                let game = Game::restore(saved_game.snapshot);
+                if let Err(err) = self.store.save(&Score::new(saved_game.level, GameState::Abandoned, &game, &saved_game.profile)) {
+                    self.messages.push(format!("Error writing score to file: {}", err));
+                }
+            }
+            Ok(None) => {}
+            Err(err) => self.messages.push(format!("Error reading saved game: {}", err)),
+        }
+    }
+
+    // games left midway are kept to be continued, from either frontend, instead of counted as abandoned
+    pub fn leave_game(&mut self, game: &mut Game) {
+        if let Some(level) = self.started_level {
+            if game.get_game_state() == GameState::Playing && game.is_started() {
+                match SavedGame::new(level, game, &self.profile).save() {
+                    Ok(()) => {
+                        self.messages.push("Game saved".to_string());
+                        self.started_level = None;
+                    }
+                    Err(err) => self.messages.push(format!("Error saving game: {}", err)),
+                }
+            }
+        }
+        self.end_game(game, GameState::Abandoned);
+    }
+
+    pub fn end_game(&mut self, game: &mut Game, game_state: GameState) {
+        if let Some(level) = self.started_level.take() {
+            let score = Score::new(level, game_state, game, &self.profile);
+            if let Some(banner) = leaderboard::personal_best(&*self.store, &score) {
+                game.set_banner(banner);
+            }
+            match self.store.save(&score) {
+                Ok(()) => self.messages.push(format!("Score written to file {:?}", game_state)),
+                Err(err) => self.messages.push(format!("Error writing score to file: {}", err)),
+            }
+        }
+    }
+
+    pub fn toggle_safe_zone(&mut self) {
+        self.safe_zone = self.safe_zone.next(&SafeZone::SELECTABLE);
+        self.save_preferences();
+    }
+
+    pub fn toggle_generation(&mut self) {
+        self.generation = self.generation.next();
+        self.save_preferences();
+    }
+
+    fn save_preferences(&mut self) {
+        if let Err(err) = config::save_preferences(&self.profile, self.safe_zone, self.generation) {
+            self.messages.push(format!("Error saving preferences: {}", err));
+        }
+    }
+
+    // the next profile brings its own bindings and preferences, it is picked again on the next start,
+    // profiles are only added in the config
+    pub fn switch_profile(&mut self) -> bool {
+        let profiles = config::profile_names();
+        if profiles.len() < 2 {
+            self.messages.push("Add profiles to config.toml as [profiles.<name>] to switch between them".to_string());
+            return false;
+        }
+        let next = profiles
+            .iter()
+            .position(|profile| *profile == self.profile)
+            .map_or(0, |i| (i + 1) % profiles.len());
+        let profile = profiles[next].clone();
+        let (controls, safe_zone, generation) = self.others.remove(&profile).unwrap_or_else(|| {
+            (config::controls(&profile), config::safe_zone(&profile), config::generation(&profile))
+        });
+        let previous = std::mem::replace(&mut self.profile, profile);
+        self.others.insert(
+            previous,
+            (
+                std::mem::replace(&mut self.controls, controls),
+                std::mem::replace(&mut self.safe_zone, safe_zone),
+                std::mem::replace(&mut self.generation, generation),
+            ),
+        );
+        if let Err(err) = config::save_profile(&self.profile) {
+            self.messages.push(format!("Error saving profile: {}", err));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::config::DEFAULT_PROFILE;
+    use crate::minezweeper::store::MemoryStore;
+
+    fn session() -> Session {
+        Session {
+            profile: DEFAULT_PROFILE.to_string(),
+            controls: Controls::default(),
+            others: HashMap::new(),
+            safe_zone: SafeZone::Opening,
+            generation: Generation::Classic,
+            store: Box::new(MemoryStore::default()),
+            started_level: Some(Level::Easy),
+            messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn games_are_scored_once() {
+        let mut session = session();
+        let mut game = Game::new((9, 9), 10, Some(1), SafeZone::Opening, Generation::Classic).unwrap();
+        session.end_game(&mut game, GameState::Lost);
+        session.end_game(&mut game, GameState::Abandoned);
+        let scores = session.store.all().unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].game_state, GameState::Lost);
+        assert_eq!(session.take_messages(), vec!["Score written to file Lost".to_string()]);
+        assert!(session.take_messages().is_empty());
+    }
+
+    // nothing was played, there is nothing to continue
+    #[test]
+    fn unstarted_games_are_abandoned_rather_than_saved() {
+        let mut session = session();
+        let mut game = Game::new((9, 9), 10, Some(1), SafeZone::Opening, Generation::Classic).unwrap();
+        session.leave_game(&mut game);
+        let scores = session.store.all().unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].game_state, GameState::Abandoned);
+    }
+}
@@ -1,11 +1,12 @@
-use super::game::GameState;
-use crate::minezweeper::Level;
+use super::game::{replay::Replay, Clicks, Game, GameState, SafeZone, Snapshot};
+use crate::minezweeper::{config, Level};
 use chrono::{DateTime, Local};
-use csv::WriterBuilder;
 use ggez::input::keyboard::KeyCode;
-use rusqlite::{Connection, params};
-use std::{error::Error, fmt::Display, fs::OpenOptions};
+use rusqlite::{Connection, Row, params};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Down,
@@ -13,61 +14,203 @@ pub enum Direction {
     Right,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
     Move(Direction),
     Clear,
     Flag,
     QuestionMark,
     ClearAdjacent,
+    Hint,
+    Pause,
     None,
 }
 
+impl Action {
+    // every action a key can be bound to, in the order they are listed
+    pub const BINDABLE: [Action; 10] = [
+        Action::Move(Direction::Up),
+        Action::Move(Direction::Down),
+        Action::Move(Direction::Left),
+        Action::Move(Direction::Right),
+        Action::Clear,
+        Action::Flag,
+        Action::QuestionMark,
+        Action::ClearAdjacent,
+        Action::Hint,
+        Action::Pause,
+    ];
+
+    // how the action is named in the config file
+    pub fn name(&self) -> String {
+        self.label().replace(' ', "_")
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Action::Move(Direction::Up) => "up",
+            Action::Move(Direction::Down) => "down",
+            Action::Move(Direction::Left) => "left",
+            Action::Move(Direction::Right) => "right",
+            Action::Clear => "clear",
+            Action::Flag => "flag",
+            Action::QuestionMark => "question mark",
+            Action::ClearAdjacent => "clear adjacent",
+            Action::Hint => "hint",
+            Action::Pause => "pause",
+            Action::None => "none",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BindingError {
+    // taken by the screens themselves
+    Reserved(KeyCode),
+    Conflict(KeyCode, Action),
+}
+
+impl Display for BindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingError::Reserved(keycode) => write!(f, "{:?} can not be bound", keycode),
+            BindingError::Conflict(keycode, action) => write!(f, "{:?} is bound to {}", keycode, action.label()),
+        }
+    }
+}
+
+impl Error for BindingError {}
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub action: Action,
+    pub keys: Vec<KeyCode>,
+}
+
+// kept in the config file as a table of action names to lists of keys
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "BTreeMap<String, Vec<KeyCode>>", into = "BTreeMap<String, Vec<KeyCode>>")]
 pub struct Controls {
-    pub up: KeyCode,
-    pub down: KeyCode,
-    pub left: KeyCode,
-    pub right: KeyCode,
-    pub clear: KeyCode,
-    pub flag: KeyCode,
-    pub question_mark: KeyCode,
-    pub clear_adjacent: KeyCode,
+    bindings: Vec<Binding>,
+}
+
+impl From<Controls> for BTreeMap<String, Vec<KeyCode>> {
+    fn from(controls: Controls) -> Self {
+        controls
+            .bindings
+            .into_iter()
+            .map(|binding| (binding.action.name(), binding.keys))
+            .collect()
+    }
+}
+
+// actions left out keep their default keys
+impl TryFrom<BTreeMap<String, Vec<KeyCode>>> for Controls {
+    type Error = String;
+
+    fn try_from(keys: BTreeMap<String, Vec<KeyCode>>) -> Result<Self, Self::Error> {
+        let mut controls = Controls::default();
+        for (name, keys) in keys.iter() {
+            let binding = controls
+                .bindings
+                .iter_mut()
+                .find(|binding| binding.action.name() == *name)
+                .ok_or(format!("unknown action `{}`", name))?;
+            binding.keys = keys.clone();
+        }
+        let mut bound: Vec<(KeyCode, Action)> = Vec::new();
+        for binding in controls.bindings.iter() {
+            for keycode in binding.keys.iter() {
+                if Self::RESERVED.contains(keycode) {
+                    return Err(BindingError::Reserved(*keycode).to_string());
+                }
+                if let Some((_, action)) = bound.iter().find(|(bound, _)| bound == keycode) {
+                    return Err(format!(
+                        "{:?} is bound to both {} and {}",
+                        keycode,
+                        action.label(),
+                        binding.action.label()
+                    ));
+                }
+                bound.push((*keycode, binding.action));
+            }
+        }
+        Ok(controls)
+    }
 }
 
 impl Controls {
+    const RESERVED: [KeyCode; 2] = [KeyCode::Back, KeyCode::Escape];
+
     pub fn default() -> Self {
+        let binding = |action: Action, keys: &[KeyCode]| Binding {
+            action,
+            keys: keys.to_vec(),
+        };
         Controls {
-            up: KeyCode::K,
-            down: KeyCode::J,
-            left: KeyCode::H,
-            right: KeyCode::L,
-            clear: KeyCode::Space,
-            flag: KeyCode::C,
-            question_mark: KeyCode::Z,
-            clear_adjacent: KeyCode::X,
+            bindings: vec![
+                binding(Action::Move(Direction::Up), &[KeyCode::K, KeyCode::Up]),
+                binding(Action::Move(Direction::Down), &[KeyCode::J, KeyCode::Down]),
+                binding(Action::Move(Direction::Left), &[KeyCode::H, KeyCode::Left]),
+                binding(Action::Move(Direction::Right), &[KeyCode::L, KeyCode::Right]),
+                binding(Action::Clear, &[KeyCode::Space]),
+                binding(Action::Flag, &[KeyCode::C]),
+                binding(Action::QuestionMark, &[KeyCode::Z]),
+                binding(Action::ClearAdjacent, &[KeyCode::X]),
+                binding(Action::Hint, &[KeyCode::V]),
+                binding(Action::Pause, &[KeyCode::P]),
+            ],
         }
     }
 
-    pub fn handle(&self, keycode: KeyCode) -> Action {
-        match keycode {
-            _ if keycode == self.up => Action::Move(Direction::Up),
-            _ if keycode == self.down => Action::Move(Direction::Down),
-            _ if keycode == self.left => Action::Move(Direction::Left),
-            _ if keycode == self.right => Action::Move(Direction::Right),
-            _ if keycode == self.clear => Action::Clear,
-            _ if keycode == self.flag => Action::Flag,
-            _ if keycode == self.question_mark => Action::QuestionMark,
-            _ if keycode == self.clear_adjacent => Action::ClearAdjacent,
-            _ => Action::None,
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    // a key only ever triggers one action
+    pub fn bind(&mut self, action: Action, keycode: KeyCode) -> Result<(), BindingError> {
+        if Self::RESERVED.contains(&keycode) {
+            return Err(BindingError::Reserved(keycode));
+        }
+        match self.handle(keycode) {
+            Action::None => {}
+            bound if bound == action => return Ok(()),
+            bound => return Err(BindingError::Conflict(keycode, bound)),
+        }
+        if let Some(binding) = self.bindings.iter_mut().find(|binding| binding.action == action) {
+            binding.keys.push(keycode);
+        }
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        if let Some(binding) = self.bindings.iter_mut().find(|binding| binding.action == action) {
+            binding.keys.clear();
         }
     }
+
+    pub fn handle(&self, keycode: KeyCode) -> Action {
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.contains(&keycode))
+            .map(|binding| binding.action)
+            .unwrap_or(Action::None)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-enum ScoreError {
+#[allow(clippy::enum_variant_names)]
+pub enum ScoreError {
     InvalidLevel,
     InvalidGameState,
+    InvalidSafeZone,
     InvalidTime,
     InvalidDateTime,
+    InvalidReplay,
 }
 
 impl Display for ScoreError {
@@ -75,156 +218,338 @@ impl Display for ScoreError {
         match self {
             ScoreError::InvalidLevel => write!(f, "Invalid level"),
             ScoreError::InvalidGameState => write!(f, "Invalid game state"),
+            ScoreError::InvalidSafeZone => write!(f, "Invalid safe zone"),
             ScoreError::InvalidTime => write!(f, "Invalid time"),
             ScoreError::InvalidDateTime => write!(f, "Invalid date time"),
+            ScoreError::InvalidReplay => write!(f, "Invalid replay"),
         }
     }
 }
 
 impl Error for ScoreError {}
 
-#[derive(Debug)]
+// an unreadable value is left out so that the score is rejected with its own error
+fn lenient<'de, D: serde::Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    Ok(T::deserialize(deserializer).ok())
+}
+
+// a score as it is exported, plain values so that a broken entry can be rejected on its own
+#[derive(Serialize, Deserialize)]
+pub struct ScoreRecord {
+    level: String,
+    game_state: String,
+    #[serde(default, deserialize_with = "lenient")]
+    time: Option<f32>,
+    date_time: String,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    safe_zone: Option<String>,
+    #[serde(default)]
+    hints: Option<usize>,
+    #[serde(default)]
+    width: Option<usize>,
+    #[serde(default)]
+    height: Option<usize>,
+    #[serde(default)]
+    mines: Option<usize>,
+    // the replay as json, like in the database
+    #[serde(default)]
+    replay: Option<String>,
+    #[serde(default)]
+    bbbv: Option<usize>,
+    #[serde(default)]
+    bbbv_per_second: Option<f32>,
+    #[serde(default)]
+    left_clicks: Option<usize>,
+    #[serde(default)]
+    right_clicks: Option<usize>,
+    #[serde(default)]
+    chord_clicks: Option<usize>,
+    #[serde(default)]
+    efficiency: Option<f32>,
+    #[serde(default)]
+    player: Option<String>,
+    #[serde(default)]
+    profile: Option<String>,
+}
+
+#[derive(Clone, Debug)]
 pub struct Score {
     pub level: Level,
     pub game_state: GameState,
     pub time: f32,
     pub date_time: DateTime<Local>,
+    pub seed: Option<u64>,
+    pub safe_zone: SafeZone,
+    pub hints: usize,
+    pub replay: Option<Replay>,
+    pub bbbv: Option<usize>,
+    pub bbbv_per_second: Option<f32>,
+    pub clicks: Option<Clicks>,
+    // the share of clicks that were needed, in percent
+    pub efficiency: Option<f32>,
+    // who played, left out for games from before names were kept
+    pub player: Option<String>,
+    pub profile: String,
 }
 
 impl Score {
-    pub fn new(level: Level, game_state: GameState, time: f32) -> Self {
+    // every field the tests do not care about left empty, they set the ones they do
+    #[cfg(test)]
+    pub fn for_test(level: Level, game_state: GameState, time: f32) -> Self {
         Score {
             level,
             game_state,
             time,
             date_time: Local::now(),
+            seed: None,
+            safe_zone: SafeZone::Opening,
+            hints: 0,
+            replay: None,
+            bbbv: None,
+            bbbv_per_second: None,
+            clicks: None,
+            efficiency: None,
+            player: None,
+            profile: config::DEFAULT_PROFILE.to_string(),
         }
     }
 
-    fn from(level: String, game_state: String, time: f32, date_time: String) -> Result<Self, ScoreError> {
-        Ok(Score {
-            level: match level.as_str() {
-                "Easy" => Level::Easy,
-                "Medium" => Level::Medium,
-                "Hard" => Level::Hard,
-                _ => return Err(ScoreError::InvalidLevel),
-            },
-            game_state: match game_state.as_str() {
-                "Won" => GameState::Won,
-                "Lost" => GameState::Lost,
-                "Abandoned" => GameState::Abandoned,
-                _ => return Err(ScoreError::InvalidGameState),
-            },
-            time,
-            date_time: date_time.parse().map_err(|_| ScoreError::InvalidDateTime)?,
-        })
+    // lost games are measured on the part of the 3BV they cleared, over the time they were played
+    pub fn new(level: Level, game_state: GameState, game: &Game, profile: &str) -> Self {
+        let bbbv = game.get_3bv();
+        let solved = game.get_solved_3bv() as f32;
+        let played = game.get_played_time();
+        let clicks = game.get_clicks();
+        Score {
+            level,
+            game_state,
+            time: game.get_final_time(),
+            date_time: Local::now(),
+            seed: Some(game.get_seed()),
+            safe_zone: game.get_safe_zone(),
+            hints: game.get_hints_used(),
+            replay: game.replay(),
+            bbbv,
+            bbbv_per_second: (bbbv.is_some() && played > 0.0).then(|| solved / played),
+            clicks: Some(clicks),
+            efficiency: (bbbv.is_some() && clicks.total() > 0).then(|| 100.0 * solved / clicks.total() as f32),
+            player: Some(config::player_name(profile)),
+            profile: profile.to_string(),
+        }
     }
 
-    #[allow(unused)]
-    fn from_csv() -> Result<Vec<Self>, Box<dyn Error>> {
-        let mut scores = Vec::new();
-        let mut reader = csv::Reader::from_path("scores.csv")?;
-        for result in reader.records() {
-            let record = result?;
-            let level = record.get(0).ok_or(ScoreError::InvalidLevel)?;
-
-            let game_state = record.get(1).ok_or(ScoreError::InvalidGameState)?;
-            
-            let time: f32 = record
-                .get(2)
-                .ok_or(ScoreError::InvalidTime)?
-                .parse()
-                .map_err(|_| ScoreError::InvalidTime)?;
-
-            let date_time = record.get(3).ok_or(ScoreError::InvalidDateTime)?;
-
-            scores.push(Score::from(level.to_string(), game_state.to_string(), time, date_time.to_string())?);
+    fn parse_level(level: &str, size: (Option<usize>, Option<usize>, Option<usize>)) -> Result<Level, ScoreError> {
+        match (level, size) {
+            ("Easy", _) => Ok(Level::Easy),
+            ("Medium", _) => Ok(Level::Medium),
+            ("Hard", _) => Ok(Level::Hard),
+            ("Custom", (Some(width), Some(height), Some(mines))) => Ok(Level::Custom { width, height, mines }),
+            _ => Err(ScoreError::InvalidLevel),
         }
-        Ok(scores)
     }
 
-    fn get_sqlite_con() -> Result<Connection, Box<dyn Error>> {
-        let new_file = !std::path::Path::new("scores.db").exists();
-        let con = Connection::open("scores.db")?;
-        
-        if new_file {
-            con.execute(
-                "CREATE TABLE score (
-                    id   INTEGER PRIMARY KEY,
-                    level TEXT NOT NULL,
-                    game_state TEXT NOT NULL,
-                    time REAL NOT NULL,
-                    date_time TEXT NOT NULL
-                )",
-                (), // empty list of parameters.
-            )?;
+    fn parse_game_state(game_state: &str) -> Result<GameState, ScoreError> {
+        match game_state {
+            "Won" => Ok(GameState::Won),
+            "Lost" => Ok(GameState::Lost),
+            "Abandoned" => Ok(GameState::Abandoned),
+            _ => Err(ScoreError::InvalidGameState),
         }
-
-        Ok(con)
     }
 
+    pub(super) fn from_row(row: &Row) -> rusqlite::Result<Result<Self, ScoreError>> {
+        let level: String = row.get(0)?;
+        let size = (row.get(7)?, row.get(8)?, row.get(9)?);
+        let game_state: String = row.get(1)?;
+        let date_time: String = row.get(3)?;
+        let safe_zone: String = row.get(5)?;
+        let time = row.get(2)?;
+        let seed = row.get::<_, Option<i64>>(4)?.map(|seed| seed as u64);
+        let hints = row.get(6)?;
+        let replay: Option<String> = row.get(10)?;
+        let clicks = row.get::<_, Option<usize>>(13)?.map(|left| -> rusqlite::Result<Clicks> {
+            Ok(Clicks {
+                left,
+                right: row.get(14)?,
+                chord: row.get(15)?,
+            })
+        });
+        let (bbbv, bbbv_per_second, efficiency) = (row.get(11)?, row.get(12)?, row.get(16)?);
+        let player = row.get(17)?;
+        let profile = row.get(18)?;
+        let clicks = clicks.transpose()?;
+        Ok((|| {
+            Ok(Score {
+                level: Self::parse_level(&level, size)?,
+                game_state: Self::parse_game_state(&game_state)?,
+                time,
+                date_time: date_time.parse().map_err(|_| ScoreError::InvalidDateTime)?,
+                seed,
+                safe_zone: SafeZone::from_name(&safe_zone).ok_or(ScoreError::InvalidSafeZone)?,
+                hints,
+                replay: replay
+                    .map(|replay| serde_json::from_str(&replay))
+                    .transpose()
+                    .map_err(|_| ScoreError::InvalidReplay)?,
+                bbbv,
+                bbbv_per_second,
+                clicks,
+                efficiency,
+                player,
+                profile,
+            })
+        })())
+    }
 
-    fn from_sqlite() -> Result<Vec<Self>, Box<dyn Error>> {
-        
-        let con = Self::get_sqlite_con()?;
-
-        let mut stmt = con.prepare("SELECT level, game_state, time, date_time FROM score")?;
-        let scores_query = stmt.query_map([], |row| {
-            Ok(Score::from(
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                row.get(3)?,
-            ))
-        })?;
-        
-        let mut scores = Vec::new();
-        for score in scores_query {
-            scores.push(score??);
+    pub fn to_record(&self) -> ScoreRecord {
+        let level_info = self.level.level_info();
+        ScoreRecord {
+            level: level_info.name,
+            game_state: self.game_state.to_string(),
+            time: Some(self.time),
+            date_time: self.date_time.to_string(),
+            seed: self.seed,
+            safe_zone: Some(self.safe_zone.to_string()),
+            hints: Some(self.hints),
+            width: Some(level_info.grid_size.0),
+            height: Some(level_info.grid_size.1),
+            mines: Some(level_info.number_of_mines),
+            replay: self.replay.as_ref().and_then(|replay| serde_json::to_string(replay).ok()),
+            bbbv: self.bbbv,
+            bbbv_per_second: self.bbbv_per_second,
+            left_clicks: self.clicks.map(|clicks| clicks.left),
+            right_clicks: self.clicks.map(|clicks| clicks.right),
+            chord_clicks: self.clicks.map(|clicks| clicks.chord),
+            efficiency: self.efficiency,
+            player: self.player.clone(),
+            profile: Some(self.profile.clone()),
         }
-        Ok(scores)
     }
 
-    pub fn all() -> Result<Vec<Self>, Box<dyn Error>> {
-        Self::from_sqlite()
+    pub fn from_record(record: ScoreRecord) -> Result<Self, ScoreError> {
+        let time = record
+            .time
+            .filter(|time| time.is_finite() && *time >= 0.0)
+            .ok_or(ScoreError::InvalidTime)?;
+        let clicks = match (record.left_clicks, record.right_clicks, record.chord_clicks) {
+            (Some(left), Some(right), Some(chord)) => Some(Clicks { left, right, chord }),
+            _ => None,
+        };
+        Ok(Score {
+            level: Self::parse_level(&record.level, (record.width, record.height, record.mines))?,
+            game_state: Self::parse_game_state(&record.game_state)?,
+            time,
+            date_time: record.date_time.parse().map_err(|_| ScoreError::InvalidDateTime)?,
+            seed: record.seed,
+            safe_zone: SafeZone::from_name(record.safe_zone.as_deref().unwrap_or("None"))
+                .ok_or(ScoreError::InvalidSafeZone)?,
+            hints: record.hints.unwrap_or(0),
+            replay: record
+                .replay
+                .filter(|replay| !replay.is_empty())
+                .map(|replay| serde_json::from_str(&replay))
+                .transpose()
+                .map_err(|_| ScoreError::InvalidReplay)?,
+            bbbv: record.bbbv,
+            bbbv_per_second: record.bbbv_per_second,
+            clicks,
+            efficiency: record.efficiency,
+            player: record.player.filter(|player| !player.is_empty()),
+            profile: record
+                .profile
+                .filter(|profile| !profile.is_empty())
+                .unwrap_or_else(|| config::DEFAULT_PROFILE.to_string()),
+        })
     }
 
-    #[allow(unused)]
-    fn save_to_csv(&self) -> Result<(), Box<dyn Error>> {
-        let new_file = !std::path::Path::new("scores.csv").exists();
+    pub(super) fn insert(&self, con: &Connection) -> Result<(), Box<dyn Error>> {
+        let level_info = self.level.level_info();
+
+        con.execute(
+            "INSERT INTO score (level, game_state, time, date_time, seed, safe_zone, hints, width, height, mines, replay,
+                bbbv, bbbv_per_second, left_clicks, right_clicks, chord_clicks, clicks, efficiency, player,
+                profile)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+            params![
+                &level_info.name,
+                &self.game_state.to_string(),
+                self.time,
+                &self.date_time.to_string(),
+                self.seed.map(|seed| seed as i64),
+                &self.safe_zone.to_string(),
+                self.hints,
+                level_info.grid_size.0,
+                level_info.grid_size.1,
+                level_info.number_of_mines,
+                self.replay.as_ref().map(serde_json::to_string).transpose()?,
+                self.bbbv,
+                self.bbbv_per_second,
+                self.clicks.map(|clicks| clicks.left),
+                self.clicks.map(|clicks| clicks.right),
+                self.clicks.map(|clicks| clicks.chord),
+                self.clicks.map(|clicks| clicks.total()),
+                self.efficiency,
+                self.player,
+                &self.profile,
+            ],
+        )?;
+        Ok(())
+    }
+}
 
-        let file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .create(true)
-            .open("scores.csv")?;
+fn default_profile() -> String {
+    config::DEFAULT_PROFILE.to_string()
+}
 
-        let mut csv_writer = WriterBuilder::new().from_writer(file);
+// a single game in progress per profile, kept across runs until it is continued or replaced
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    pub level: Level,
+    pub snapshot: Snapshot,
+    // games saved before profiles were kept belong to the default one
+    #[serde(default = "default_profile")]
+    pub profile: String,
+}
 
-        if new_file {
-            csv_writer.write_record(&["level", "game_state", "time", "date_time"])?;
+impl SavedGame {
+    pub fn new(level: Level, game: &Game, profile: &str) -> Self {
+        SavedGame {
+            level,
+            snapshot: game.snapshot(),
+            profile: profile.to_string(),
         }
-
-        csv_writer.write_record(&[
-            self.level.level_info().name,
-            self.game_state.to_string(),
-            self.time.to_string(),
-            self.date_time.to_string(),
-        ])?;
-        Ok(())
     }
 
-    fn save_to_sqlite(&self) -> Result<(), Box<dyn Error>> {
-        let con = Self::get_sqlite_con()?;
+    pub fn exists(profile: &str) -> bool {
+        config::saved_game_path(profile).exists()
+    }
 
-        con.execute(
-            "INSERT INTO score (level, game_state, time, date_time) VALUES (?1, ?2, ?3, ?4)",
-            params![&self.level.level_info().name, &self.game_state.to_string(), self.time, &self.date_time.to_string()],
-        )?;
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = config::saved_game_path(&self.profile);
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
         Ok(())
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        self.save_to_sqlite()
+    pub fn load(profile: &str) -> Result<Option<Self>, Box<dyn Error>> {
+        if !Self::exists(profile) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(config::saved_game_path(profile))?)?))
+    }
+
+    // the file is removed so the same game can not be continued twice
+    pub fn take(profile: &str) -> Result<Option<Self>, Box<dyn Error>> {
+        if !Self::exists(profile) {
+            return Ok(None);
+        }
+        let path = config::saved_game_path(profile);
+        let saved_game = serde_json::from_str(&fs::read_to_string(&path)?);
+        fs::remove_file(path)?;
+        Ok(Some(saved_game?))
     }
 }
@@ -0,0 +1,243 @@
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use csv::WriterBuilder;
+use rusqlite::Connection;
+
+use crate::minezweeper::config::{self, StoreKind};
+use crate::minezweeper::{migrations, settings::Score, transfer};
+
+// where older versions kept their scores, relative to the working directory
+const STRAY_DATABASE_PATH: &str = "scores.db";
+const STRAY_CSV_PATH: &str = "scores.csv";
+
+pub trait ScoreStore {
+    // in the order they were saved, the cli numbers games by it
+    fn all(&self) -> Result<Vec<Score>, Box<dyn Error>>;
+
+    fn save(&mut self, score: &Score) -> Result<(), Box<dyn Error>>;
+
+    fn save_all(&mut self, scores: &[Score]) -> Result<(), Box<dyn Error>> {
+        for score in scores {
+            self.save(score)?;
+        }
+        Ok(())
+    }
+}
+
+// the store picked in the config, scores are kept in memory when it can not be opened
+pub fn open() -> Box<dyn ScoreStore> {
+    let path = config::database_path();
+    let store: Result<Box<dyn ScoreStore>, Box<dyn Error>> = match config::get().store {
+        StoreKind::Sqlite => SqliteStore::open(&path).map(|store| Box::new(store) as Box<dyn ScoreStore>),
+        StoreKind::Csv => CsvStore::open(path).map(|store| Box::new(store) as Box<dyn ScoreStore>),
+        StoreKind::Memory => Ok(Box::new(MemoryStore::default())),
+    };
+    store.unwrap_or_else(|err| {
+        println!("Error opening scores, they will not be kept: {}", err);
+        Box::new(MemoryStore::default())
+    })
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+// every entry of the stray csv that can be read
+fn stray_csv_scores() -> Result<Vec<Score>, Box<dyn Error>> {
+    let mut scores = Vec::new();
+    for (entry, score) in transfer::read(Path::new(STRAY_CSV_PATH))?.into_iter().enumerate() {
+        match score {
+            Ok(score) => scores.push(score),
+            Err(err) => println!("Skipped entry {} of {}: {}", entry + 1, STRAY_CSV_PATH, err),
+        }
+    }
+    println!("Imported scores from {}", STRAY_CSV_PATH);
+    Ok(scores)
+}
+
+pub struct SqliteStore {
+    con: Connection,
+}
+
+impl SqliteStore {
+    // scores used to be kept next to wherever the game was launched, they are brought over once
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        create_parent_dir(path)?;
+        let stray = Path::new(STRAY_DATABASE_PATH);
+        if !path.exists() && stray.exists() {
+            fs::copy(stray, path)?;
+            println!("Imported scores from {}", stray.display());
+        }
+        let new_file = !path.exists();
+        let mut con = Connection::open(path)?;
+        migrations::migrate(&mut con)?;
+        let mut store = SqliteStore { con };
+
+        // the csv only counts when there was no stray database to copy
+        if new_file && Path::new(STRAY_CSV_PATH).exists() {
+            store.save_all(&stray_csv_scores()?)?;
+        }
+        Ok(store)
+    }
+}
+
+impl ScoreStore for SqliteStore {
+    fn all(&self) -> Result<Vec<Score>, Box<dyn Error>> {
+        let mut stmt = self.con.prepare(
+            "SELECT level, game_state, time, date_time, seed, safe_zone, hints, width, height, mines, replay,
+                bbbv, bbbv_per_second, left_clicks, right_clicks, chord_clicks, efficiency, player, profile FROM score
+                ORDER BY id",
+        )?;
+        let scores_query = stmt.query_map([], Score::from_row)?;
+
+        // one unreadable row is reported and skipped, the rest of the history still shows
+        let mut scores = Vec::new();
+        for (row, score) in scores_query.enumerate() {
+            match score {
+                Ok(Ok(score)) => scores.push(score),
+                Ok(Err(err)) => println!("Skipped row {} of the scores: {}", row + 1, err),
+                Err(err) => println!("Skipped row {} of the scores: {}", row + 1, err),
+            }
+        }
+        Ok(scores)
+    }
+
+    fn save(&mut self, score: &Score) -> Result<(), Box<dyn Error>> {
+        score.insert(&self.con)
+    }
+
+    // all or nothing, a failed import leaves the database as it was
+    fn save_all(&mut self, scores: &[Score]) -> Result<(), Box<dyn Error>> {
+        let tx = self.con.transaction()?;
+        for score in scores {
+            score.insert(&tx)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+// the same columns as a csv export, for when sqlite can not be used
+pub struct CsvStore {
+    path: PathBuf,
+}
+
+impl CsvStore {
+    pub fn open(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        create_parent_dir(&path)?;
+        let mut store = CsvStore { path };
+        if !store.path.exists() && Path::new(STRAY_CSV_PATH).exists() {
+            store.save_all(&stray_csv_scores()?)?;
+        }
+        Ok(store)
+    }
+}
+
+impl ScoreStore for CsvStore {
+    // unreadable rows are left out rather than hiding the whole history
+    fn all(&self) -> Result<Vec<Score>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut scores = Vec::new();
+        for (entry, score) in transfer::read(&self.path)?.into_iter().enumerate() {
+            match score {
+                Ok(score) => scores.push(score),
+                Err(err) => println!("Skipped entry {} of {}: {}", entry + 1, self.path.display(), err),
+            }
+        }
+        Ok(scores)
+    }
+
+    fn save(&mut self, score: &Score) -> Result<(), Box<dyn Error>> {
+        self.save_all(std::slice::from_ref(score))
+    }
+
+    // a file written by a version with other columns is rewritten rather than appended to
+    fn save_all(&mut self, scores: &[Score]) -> Result<(), Box<dyn Error>> {
+        let Some(first) = scores.first() else {
+            return Ok(());
+        };
+        let outdated = self.path.exists() && csv::Reader::from_path(&self.path)?.headers()? != &header(first)?;
+        let scores = if outdated {
+            // the rows that can not be read would be lost, the file is left alone until they are fixed
+            let mut kept = Vec::new();
+            for (entry, score) in transfer::read(&self.path)?.into_iter().enumerate() {
+                kept.push(score.map_err(|err| {
+                    format!("{} needs upgrading but entry {} can not be read: {}", self.path.display(), entry + 1, err)
+                })?);
+            }
+            kept.into_iter().chain(scores.iter().cloned()).collect()
+        } else {
+            scores.to_vec()
+        };
+        let new_file = outdated || !self.path.exists();
+        let file = OpenOptions::new()
+            .write(true)
+            .append(!new_file)
+            .truncate(new_file)
+            .create(true)
+            .open(&self.path)?;
+        let mut writer = WriterBuilder::new().has_headers(new_file).from_writer(file);
+        for score in scores {
+            writer.serialize(score.to_record())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// the header line as this version writes it
+fn header(score: &Score) -> Result<csv::StringRecord, Box<dyn Error>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.serialize(score.to_record())?;
+    let data = writer.into_inner()?;
+    Ok(csv::Reader::from_reader(data.as_slice()).headers()?.clone())
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    scores: Vec<Score>,
+}
+
+impl ScoreStore for MemoryStore {
+    fn all(&self) -> Result<Vec<Score>, Box<dyn Error>> {
+        Ok(self.scores.clone())
+    }
+
+    fn save(&mut self, score: &Score) -> Result<(), Box<dyn Error>> {
+        self.scores.push(score.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::game::GameState;
+    use crate::minezweeper::Level;
+
+    #[test]
+    fn unreadable_rows_are_not_lost_when_upgrading() {
+        let dir = std::env::temp_dir().join(format!("minezweeper-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scores.csv");
+        // the header of a version without profiles, with one good and one broken row
+        let outdated = "level,game_state,time,date_time\n\
+            Easy,Won,10.0,2024-01-01T10:00:00+00:00\n\
+            Easy,Won,not a time,2024-01-02T10:00:00+00:00\n";
+        fs::write(&path, outdated).unwrap();
+
+        let mut store = CsvStore::open(path.clone()).unwrap();
+        assert_eq!(store.all().unwrap().len(), 1);
+        let err = store.save(&Score::for_test(Level::Easy, GameState::Lost, 5.0)).unwrap_err();
+        assert!(err.to_string().contains("entry 2"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), outdated);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
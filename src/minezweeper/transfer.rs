@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::minezweeper::config;
+use crate::minezweeper::settings::{Score, ScoreRecord};
+use crate::minezweeper::store::ScoreStore;
+
+// exports from the settings screen are written next to the database, imports are picked up from there
+const EXPORT_NAME: &str = "export";
+const IMPORT_NAME: &str = "import";
+
+#[derive(Copy, Clone)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    const ALL: [Format; 2] = [Format::Csv, Format::Json];
+
+    fn extension(&self) -> &str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+
+    fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Ok(Format::Csv),
+            Some("json") => Ok(Format::Json),
+            _ => Err(format!("Unknown format for {}, use a .csv or .json file", path.display()).into()),
+        }
+    }
+}
+
+pub struct ImportReport {
+    pub imported: usize,
+    pub duplicates: usize,
+    // the file, the entry number in it counted from 1, and why it was rejected
+    pub rejected: Vec<(String, usize, String)>,
+}
+
+impl ImportReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "Imported {}, {} duplicates, {} rejected",
+            self.imported,
+            self.duplicates,
+            self.rejected.len()
+        )
+    }
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary())?;
+        for (file, entry, reason) in &self.rejected {
+            write!(f, "\n  {} entry {}: {}", file, entry, reason)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn transfer_dir() -> PathBuf {
+    config::database_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+// every entry of the file, each one parsed and validated on its own
+pub fn read(path: &Path) -> Result<Vec<Result<Score, String>>, Box<dyn Error>> {
+    let records: Vec<Result<ScoreRecord, String>> = match Format::from_path(path)? {
+        Format::Csv => csv::Reader::from_path(path)?
+            .deserialize()
+            .map(|record| record.map_err(|err| err.to_string()))
+            .collect(),
+        Format::Json => serde_json::from_str::<Vec<serde_json::Value>>(&fs::read_to_string(path)?)?
+            .into_iter()
+            .map(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+            .collect(),
+    };
+    Ok(records
+        .into_iter()
+        .map(|record| Score::from_record(record?).map_err(|err| err.to_string()))
+        .collect())
+}
+
+pub fn export(store: &dyn ScoreStore, path: &Path) -> Result<usize, Box<dyn Error>> {
+    let records: Vec<ScoreRecord> = store.all()?.iter().map(Score::to_record).collect();
+    match Format::from_path(path)? {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_path(path)?;
+            for record in &records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+        Format::Json => fs::write(path, serde_json::to_string_pretty(&records)?)?,
+    }
+    Ok(records.len())
+}
+
+// a score that is already stored, same level at the same time, is skipped
+pub fn import(store: &mut dyn ScoreStore, path: &Path) -> Result<ImportReport, Box<dyn Error>> {
+    let mut known: HashSet<_> = store.all()?
+        .into_iter()
+        .map(|score| (score.date_time, score.level))
+        .collect();
+    let mut report = ImportReport {
+        imported: 0,
+        duplicates: 0,
+        rejected: Vec::new(),
+    };
+    let file = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+    let mut scores = Vec::new();
+    for (entry, score) in read(path)?.into_iter().enumerate() {
+        match score {
+            Ok(score) if known.insert((score.date_time, score.level)) => scores.push(score),
+            Ok(_) => report.duplicates += 1,
+            Err(reason) => report.rejected.push((file.clone(), entry + 1, reason)),
+        }
+    }
+    store.save_all(&scores)?;
+    report.imported = scores.len();
+    Ok(report)
+}
+
+// writes both formats, returns the directory they were written to
+pub fn export_all(store: &dyn ScoreStore) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = transfer_dir();
+    for format in Format::ALL {
+        export(store, &dir.join(EXPORT_NAME).with_extension(format.extension()))?;
+    }
+    Ok(dir)
+}
+
+// imports every import file found next to the database
+pub fn import_all(store: &mut dyn ScoreStore) -> Result<ImportReport, Box<dyn Error>> {
+    let dir = transfer_dir();
+    let paths: Vec<PathBuf> = Format::ALL
+        .iter()
+        .map(|format| dir.join(IMPORT_NAME).with_extension(format.extension()))
+        .filter(|path| path.exists())
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("No {}.csv or {}.json in {}", IMPORT_NAME, IMPORT_NAME, dir.display()).into());
+    }
+    let mut total = ImportReport {
+        imported: 0,
+        duplicates: 0,
+        rejected: Vec::new(),
+    };
+    for path in paths {
+        let report = import(store, &path)?;
+        total.imported += report.imported;
+        total.duplicates += report.duplicates;
+        total.rejected.extend(report.rejected);
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minezweeper::store::MemoryStore;
+
+    #[test]
+    fn rejections_say_which_file_they_come_from() {
+        let dir = std::env::temp_dir().join(format!("minezweeper-transfer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (csv, json) = (dir.join("import.csv"), dir.join("import.json"));
+        fs::write(&csv, "level\nEasy\n").unwrap();
+        fs::write(&json, "[{}]").unwrap();
+
+        let mut store = MemoryStore::default();
+        let mut report = import(&mut store, &csv).unwrap();
+        report.rejected.extend(import(&mut store, &json).unwrap().rejected);
+        let shown = report.to_string();
+        assert!(shown.contains("import.csv entry 1:"));
+        assert!(shown.contains("import.json entry 1:"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
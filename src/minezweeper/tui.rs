@@ -0,0 +1,567 @@
+use std::error::Error;
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::{
+    cursor,
+    event::{
+        self, Event, KeyCode as TermKey, KeyEvent, KeyEventKind, KeyModifiers, MouseButton as TermButton, MouseEvent,
+        MouseEventKind,
+    },
+    execute, queue,
+    style::{self, Color as TermColor},
+    terminal,
+};
+use ggez::graphics::Color;
+use ggez::input::{keyboard::KeyCode, mouse::MouseButton};
+
+use crate::minezweeper::{
+    config::colors,
+    game::{Face, Game, GameState},
+    session::Session,
+    settings::Action,
+    Launch, Level,
+};
+
+// a cell takes three columns so the board comes out about square
+const CELL_WIDTH: u16 = 3;
+// the counters take the first row, the board starts below a blank one
+const BOARD_TOP: u16 = 2;
+// the banner, the hint, the message and the keys go below the board
+const FOOTER_ROWS: u16 = 5;
+// the clock is redrawn this often when nothing happens
+const TICK: Duration = Duration::from_millis(250);
+const LEVELS: [Level; 3] = [Level::Easy, Level::Medium, Level::Hard];
+// the actions listed under the board, moving is left to the arrows
+const LISTED: [Action; 5] = [Action::Clear, Action::Flag, Action::ClearAdjacent, Action::Hint, Action::Pause];
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+];
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+const FUNCTION_KEYS: [KeyCode; 12] = [
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8,
+    KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+];
+
+// terminal keys under the names the window gives them, so both read the same bindings
+fn keycode(key: TermKey) -> Option<KeyCode> {
+    match key {
+        TermKey::Char(c) if c.is_ascii_alphabetic() => Some(LETTERS[(c.to_ascii_lowercase() as u8 - b'a') as usize]),
+        TermKey::Char(c) if c.is_ascii_digit() => Some(DIGITS[(c as u8 - b'0') as usize]),
+        TermKey::Char(' ') => Some(KeyCode::Space),
+        TermKey::Char(',') => Some(KeyCode::Comma),
+        TermKey::Char('.') => Some(KeyCode::Period),
+        TermKey::Char('/') => Some(KeyCode::Slash),
+        TermKey::Char(';') => Some(KeyCode::Semicolon),
+        TermKey::Char('\'') => Some(KeyCode::Apostrophe),
+        TermKey::Char('-') => Some(KeyCode::Minus),
+        TermKey::Char('=') => Some(KeyCode::Equals),
+        TermKey::Char('[') => Some(KeyCode::LBracket),
+        TermKey::Char(']') => Some(KeyCode::RBracket),
+        TermKey::Char('\\') => Some(KeyCode::Backslash),
+        TermKey::Char('`') => Some(KeyCode::Grave),
+        TermKey::F(n) if (1..=12).contains(&n) => Some(FUNCTION_KEYS[n as usize - 1]),
+        TermKey::Up => Some(KeyCode::Up),
+        TermKey::Down => Some(KeyCode::Down),
+        TermKey::Left => Some(KeyCode::Left),
+        TermKey::Right => Some(KeyCode::Right),
+        TermKey::Enter => Some(KeyCode::Return),
+        TermKey::Backspace => Some(KeyCode::Back),
+        TermKey::Esc => Some(KeyCode::Escape),
+        TermKey::Tab => Some(KeyCode::Tab),
+        TermKey::Delete => Some(KeyCode::Delete),
+        TermKey::Insert => Some(KeyCode::Insert),
+        TermKey::Home => Some(KeyCode::Home),
+        TermKey::End => Some(KeyCode::End),
+        TermKey::PageUp => Some(KeyCode::PageUp),
+        TermKey::PageDown => Some(KeyCode::PageDown),
+        _ => None,
+    }
+}
+
+fn color(color: Color) -> TermColor {
+    let (r, g, b) = color.to_rgb();
+    TermColor::Rgb { r, g, b }
+}
+
+// raw mode and the alternate screen are left again however the game ends, panics included
+struct Terminal {
+    out: Stdout,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stdout();
+        execute!(
+            out,
+            terminal::EnterAlternateScreen,
+            event::EnableMouseCapture,
+            event::EnableFocusChange,
+            cursor::Hide
+        )?;
+        Ok(Terminal { out })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            self.out,
+            cursor::Show,
+            event::DisableFocusChange,
+            event::DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// whether a board of this size is drawn whole in a terminal of this size
+fn fits(shape: (usize, usize), terminal_size: (u16, u16)) -> bool {
+    let (width, height) = shape;
+    let (columns, rows) = terminal_size;
+    width * CELL_WIDTH as usize <= columns as usize && height + (BOARD_TOP + FOOTER_ROWS) as usize <= rows as usize
+}
+
+// a board that no longer fits once the terminal shrank takes no input until it grows again
+fn hidden(game: &Game) -> bool {
+    terminal::size().is_ok_and(|terminal_size| !fits(game.get_shape(), terminal_size))
+}
+
+#[allow(clippy::large_enum_variant)]
+enum Screen {
+    Menu,
+    // the size of a custom level as it is typed, WxH:M
+    Custom(String),
+    Game(Game),
+}
+
+// the same games as the window, played with the keyboard and the mouse of a terminal
+struct Tui {
+    screen: Screen,
+    session: Session,
+    // what went on last, shown at the bottom since nothing can be printed
+    message: Option<String>,
+    quit: bool,
+}
+
+impl Tui {
+    fn new() -> Self {
+        Tui {
+            screen: Screen::Menu,
+            session: Session::new(),
+            message: None,
+            quit: false,
+        }
+    }
+
+    // the session can report several things at once, there is room for the last one
+    fn show_messages(&mut self) {
+        if let Some(message) = self.session.take_messages().pop() {
+            self.message = Some(message);
+        }
+    }
+
+    // boards larger than the terminal are refused rather than cut off
+    fn fits(&mut self, shape: (usize, usize)) -> bool {
+        let terminal_size = terminal::size().unwrap_or((u16::MAX, u16::MAX));
+        if fits(shape, terminal_size) {
+            return true;
+        }
+        self.message = Some(format!(
+            "a {}x{} board needs a terminal of {}x{}, this one is {}x{}",
+            shape.0,
+            shape.1,
+            shape.0 * CELL_WIDTH as usize,
+            shape.1 + (BOARD_TOP + FOOTER_ROWS) as usize,
+            terminal_size.0,
+            terminal_size.1
+        ));
+        false
+    }
+
+    fn start_game(&mut self, level: Level, seed: Option<u64>) {
+        if !self.fits(level.level_info().grid_size) {
+            return;
+        }
+        match self.session.new_game(level, seed) {
+            Ok(mut game) => {
+                // there is no pointer to start from, the keys move from the middle
+                let (width, height) = game.get_shape();
+                game.hover(Some((width / 2, height / 2)));
+                self.message = None;
+                self.screen = Screen::Game(game);
+            }
+            Err(error) => self.message = Some(error.to_string()),
+        }
+    }
+
+    // a saved game too large for the terminal stays saved for the window or a larger terminal
+    fn continue_game(&mut self) {
+        if let Some(level) = self.session.saved_level() {
+            if !self.fits(level.level_info().grid_size) {
+                return;
+            }
+        }
+        if let Some(game) = self.session.continue_game() {
+            self.message = None;
+            self.screen = Screen::Game(game);
+        }
+    }
+
+    fn leave_game(&mut self) {
+        if let Screen::Game(game) = &mut self.screen {
+            self.session.leave_game(game);
+        }
+        self.screen = Screen::Menu;
+    }
+
+    fn key_event(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == TermKey::Char('c') {
+            self.leave_game();
+            self.quit = true;
+            return;
+        }
+        // the size is typed as text, the bindings play no part in it
+        if let Screen::Custom(text) = &mut self.screen {
+            match key.code {
+                TermKey::Char(c) => text.push(c),
+                TermKey::Backspace => {
+                    text.pop();
+                }
+                TermKey::Esc => self.screen = Screen::Menu,
+                TermKey::Enter => match text.parse::<Level>() {
+                    Ok(level) => self.start_game(level, None),
+                    Err(err) => self.message = Some(err),
+                },
+                _ => {}
+            }
+            return;
+        }
+        let Some(keycode) = keycode(key.code) else {
+            return;
+        };
+        match &mut self.screen {
+            Screen::Menu => match keycode {
+                KeyCode::Key1 => self.start_game(Level::Easy, None),
+                KeyCode::Key2 => self.start_game(Level::Medium, None),
+                KeyCode::Key3 => self.start_game(Level::Hard, None),
+                KeyCode::C => {
+                    self.message = None;
+                    self.screen = Screen::Custom(String::new());
+                }
+                KeyCode::R => self.continue_game(),
+                KeyCode::F => self.session.toggle_safe_zone(),
+                KeyCode::G => self.session.toggle_generation(),
+                KeyCode::P => {
+                    self.session.switch_profile();
+                }
+                KeyCode::Q | KeyCode::Escape => self.quit = true,
+                _ => {}
+            },
+            Screen::Game(_) if keycode == KeyCode::Back || keycode == KeyCode::Escape => {
+                self.message = None;
+                self.leave_game();
+            }
+            Screen::Game(game) if hidden(game) => {}
+            Screen::Game(game) => {
+                let game_state = game.handle(self.session.controls.handle(keycode));
+                if game_state != GameState::Playing {
+                    self.session.end_game(game, game_state);
+                }
+            }
+            Screen::Custom(_) => {}
+        }
+    }
+
+    fn mouse_event(&mut self, mouse: MouseEvent) {
+        let Screen::Game(game) = &mut self.screen else {
+            return;
+        };
+        if hidden(game) {
+            return;
+        }
+        let (width, height) = game.get_shape();
+        let cell = (mouse.row >= BOARD_TOP)
+            .then(|| ((mouse.column / CELL_WIDTH) as usize, (mouse.row - BOARD_TOP) as usize))
+            .filter(|&(x, y)| x < width && y < height);
+        let button = |button: TermButton| match button {
+            TermButton::Left => MouseButton::Left,
+            TermButton::Right => MouseButton::Right,
+            TermButton::Middle => MouseButton::Middle,
+        };
+        match mouse.kind {
+            MouseEventKind::Moved | MouseEventKind::Drag(_) => game.hover(cell),
+            MouseEventKind::Down(_) => {
+                game.hover(cell);
+                game.press(cell);
+            }
+            MouseEventKind::Up(pressed) => {
+                let game_state = game.click(button(pressed), cell);
+                if game_state != GameState::Playing {
+                    self.session.end_game(game, game_state);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_menu(&self, out: &mut Stdout) -> io::Result<()> {
+        let mut lines = vec![("minezweeper".to_string(), colors().flag), (String::new(), colors().text)];
+        for (key, level) in LEVELS.iter().enumerate() {
+            let level_info = level.level_info();
+            lines.push((
+                format!(
+                    "{}  {:<8}{}x{}, {} mines",
+                    key + 1,
+                    level.label(),
+                    level_info.grid_size.0,
+                    level_info.grid_size.1,
+                    level_info.number_of_mines
+                ),
+                colors().text,
+            ));
+        }
+        lines.push(("c  custom".to_string(), colors().text));
+        if self.session.has_saved_game() {
+            lines.push(("r  continue".to_string(), colors().text));
+        }
+        lines.push((String::new(), colors().text));
+        lines.push((format!("f  first click: {}", self.session.safe_zone.label()), colors().text));
+        lines.push((format!("g  {}", self.session.generation.label()), colors().text));
+        lines.push((format!("p  profile: {}", self.session.profile), colors().text));
+        lines.push(("q  quit".to_string(), colors().text));
+        lines.push((String::new(), colors().text));
+        if let Some(message) = &self.message {
+            lines.push((message.clone(), colors().question_mark));
+        }
+        Self::draw_lines(out, lines)
+    }
+
+    fn draw_custom(&self, out: &mut Stdout, text: &str) -> io::Result<()> {
+        let mut lines = vec![
+            ("custom level".to_string(), colors().flag),
+            (String::new(), colors().text),
+            (format!("size and mines, WxH:M  {}_", text), colors().text),
+            (String::new(), colors().text),
+            ("enter play   esc menu".to_string(), colors().text),
+        ];
+        if let Some(message) = &self.message {
+            lines.push((message.clone(), colors().question_mark));
+        }
+        Self::draw_lines(out, lines)
+    }
+
+    fn draw_lines(out: &mut Stdout, lines: Vec<(String, Color)>) -> io::Result<()> {
+        queue!(out, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::CurrentLine))?;
+        for (row, (line, line_color)) in lines.into_iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(0, row as u16 + 1),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                style::SetForegroundColor(color(line_color)),
+                style::Print(format!(" {}", line)),
+                style::ResetColor
+            )?;
+        }
+        queue!(out, cursor::MoveToNextLine(1), terminal::Clear(terminal::ClearType::FromCursorDown))
+    }
+
+    fn draw_header(out: &mut Stdout, game: &Game) -> io::Result<()> {
+        let width = game.get_shape().0 * CELL_WIDTH as usize;
+        let mines = game.get_remaining_mines().to_string();
+        let clock = format!("{:.0}", game.clock());
+        let status = format!("{:^1$}", game.status(), width.saturating_sub(mines.len() + clock.len()));
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            style::SetForegroundColor(color(colors().flag)),
+            style::Print(mines),
+            style::SetForegroundColor(color(colors().text)),
+            style::Print(status),
+            style::SetForegroundColor(color(colors().flag)),
+            style::Print(clock),
+            style::ResetColor
+        )
+    }
+
+    fn draw_game(&self, out: &mut Stdout, game: &Game) -> io::Result<()> {
+        Self::draw_header(out, game)?;
+        queue!(out, cursor::MoveTo(0, 1), terminal::Clear(terminal::ClearType::CurrentLine))?;
+        let (width, height) = game.get_shape();
+        let hint = game.get_hint();
+        for y in 0..height {
+            queue!(out, cursor::MoveTo(0, BOARD_TOP + y as u16))?;
+            for x in 0..width {
+                let (symbol, foreground) = match game.face(x, y) {
+                    Face::Number(value) => (char::from(b'0' + value as u8), colors().numbers[value - 1]),
+                    Face::Mine => ('*', colors().mine),
+                    Face::Flag => ('F', colors().flag),
+                    Face::QuestionMark => ('?', colors().question_mark),
+                    Face::Blank => (' ', colors().text),
+                };
+                let (left, right) = match hint {
+                    Some((hinted, _)) if hinted == (x, y) => ('[', ']'),
+                    _ => (' ', ' '),
+                };
+                queue!(
+                    out,
+                    style::SetBackgroundColor(color(game.cell_color(x, y))),
+                    style::SetForegroundColor(color(colors().question_mark)),
+                    style::Print(left),
+                    style::SetForegroundColor(color(foreground)),
+                    style::Print(symbol),
+                    style::SetForegroundColor(color(colors().question_mark)),
+                    style::Print(right),
+                )?;
+            }
+            queue!(out, style::ResetColor, terminal::Clear(terminal::ClearType::UntilNewLine))?;
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, BOARD_TOP + height as u16),
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )?;
+
+        let mut lines = Vec::new();
+        if let Some(banner) = game.get_banner() {
+            lines.push((banner.to_string(), colors().flag));
+        }
+        if let Some((_, Some(probability))) = hint {
+            lines.push((format!("no safe cell, the hint is a mine {:.0}% of the time", 100.0 * probability), colors().question_mark));
+        }
+        if let Some(message) = &self.message {
+            lines.push((message.clone(), colors().question_mark));
+        }
+        let keys: Vec<String> = LISTED
+            .iter()
+            .filter_map(|action| {
+                self.session
+                    .controls
+                    .keys(*action)
+                    .first()
+                    .map(|keycode| format!("{} {:?}", action.label(), keycode))
+            })
+            .collect();
+        lines.push((format!("{}   move with the arrows or the mouse   esc menu", keys.join("   ")), colors().text));
+        for (row, (line, line_color)) in lines.into_iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(0, BOARD_TOP + height as u16 + 1 + row as u16),
+                style::SetForegroundColor(color(line_color)),
+                style::Print(line),
+                style::ResetColor
+            )?;
+        }
+        Ok(())
+    }
+
+    // the board is not drawn cut off, the game waits paused for the terminal to grow
+    fn draw_too_small(out: &mut Stdout) -> io::Result<()> {
+        Self::draw_lines(
+            out,
+            vec![
+                ("the terminal is too small for this board".to_string(), colors().flag),
+                ("make it larger to go on, esc saves the game for later".to_string(), colors().text),
+            ],
+        )
+    }
+
+    // every line is written over in place, clearing the whole screen flickers over ssh
+    fn draw(&self, out: &mut Stdout) -> io::Result<()> {
+        match &self.screen {
+            Screen::Menu => self.draw_menu(out)?,
+            Screen::Custom(text) => self.draw_custom(out, text)?,
+            Screen::Game(game) if hidden(game) => Self::draw_too_small(out)?,
+            Screen::Game(game) => self.draw_game(out, game)?,
+        }
+        out.flush()
+    }
+
+    fn hovered_cell(&self) -> Option<(usize, usize)> {
+        match &self.screen {
+            Screen::Game(game) => game.get_hovered_cell(),
+            Screen::Menu | Screen::Custom(_) => None,
+        }
+    }
+
+    // the whole screen is drawn again after input, only the clock while waiting for it
+    fn run(&mut self, terminal: &mut Terminal) -> Result<(), Box<dyn Error>> {
+        self.draw(&mut terminal.out)?;
+        while !self.quit {
+            if !event::poll(TICK)? {
+                match &self.screen {
+                    Screen::Game(game) if !hidden(game) => {
+                        Self::draw_header(&mut terminal.out, game)?;
+                        terminal.out.flush()?;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => self.key_event(key),
+                // moving within a cell changes nothing on screen
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved => {
+                    let hovered = self.hovered_cell();
+                    self.mouse_event(mouse);
+                    if hovered == self.hovered_cell() {
+                        continue;
+                    }
+                }
+                Event::Mouse(mouse) => self.mouse_event(mouse),
+                // a game is paused as soon as the terminal is left for another window
+                Event::FocusLost => {
+                    if let Screen::Game(game) = &mut self.screen {
+                        game.pause();
+                    }
+                }
+                Event::Resize(columns, rows) => {
+                    if let Screen::Game(game) = &mut self.screen {
+                        if !fits(game.get_shape(), (columns, rows)) {
+                            game.pause();
+                        }
+                    }
+                    execute!(terminal.out, terminal::Clear(terminal::ClearType::All))?
+                }
+                _ => {}
+            }
+            self.show_messages();
+            self.draw(&mut terminal.out)?;
+        }
+        Ok(())
+    }
+}
+
+// replays are left to the window, the terminal has no player for them
+pub fn run(launch: Launch) -> Result<(), Box<dyn Error>> {
+    let mut tui = Tui::new();
+    match launch {
+        Launch::Menu => {}
+        Launch::Game(level, seed) => tui.start_game(level, seed),
+        Launch::Replay(_) => return Err("Replays can only be watched in the window, leave out --tui".into()),
+    }
+    tui.show_messages();
+    let mut terminal = Terminal::enter()?;
+    tui.run(&mut terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_fit_with_room_for_the_counters_and_the_keys() {
+        assert!(fits((30, 16), (90, 23)));
+        assert!(!fits((30, 16), (89, 23)));
+        assert!(!fits((30, 16), (90, 22)));
+    }
+}